@@ -0,0 +1,500 @@
+use crate::feeder::{FeedEvent, TokenOrPairAddress, TokenOrPairPriceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use strum_macros::Display;
+
+#[derive(Display, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Direction {
+    Above,
+    Below,
+}
+
+/// What an `AlertRule` watches. `address` is the feeder's `TokenOrPairAddress`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum AlertCondition {
+    /// The price moves from one side of `level` to the other (`Above`: crosses upwards).
+    PriceCross {
+        address: TokenOrPairAddress,
+        level: f64,
+        direction: Direction,
+    },
+    /// The price changes by at least `percent` (either way) within `window_secs`.
+    PercentChange {
+        address: TokenOrPairAddress,
+        percent: f64,
+        window_secs: u64,
+    },
+    /// A pair ratio is more than `max_deviation_percent` away from `expected`
+    /// (e.g. a JupSOL/SOL depeg).
+    PairDeviation {
+        address: TokenOrPairAddress,
+        expected: f64,
+        max_deviation_percent: f64,
+    },
+    /// Perps PnL in USD is above/below `threshold_usd`.
+    PerpPnl {
+        address: TokenOrPairAddress,
+        threshold_usd: f64,
+        direction: Direction,
+    },
+}
+
+impl AlertCondition {
+    pub fn address(&self) -> &str {
+        match self {
+            AlertCondition::PriceCross { address, .. }
+            | AlertCondition::PercentChange { address, .. }
+            | AlertCondition::PairDeviation { address, .. }
+            | AlertCondition::PerpPnl { address, .. } => address,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub id: String,
+    pub condition: AlertCondition,
+    /// Minimum seconds between two alerts of this rule.
+    #[serde(default)]
+    pub cooldown_secs: u64,
+    /// After firing, the condition must clear by this percent of its threshold
+    /// before the rule re-arms (avoids flapping around the level).
+    #[serde(default)]
+    pub hysteresis_percent: f64,
+}
+
+impl AlertRule {
+    pub fn new(id: &str, condition: AlertCondition) -> Self {
+        Self {
+            id: id.to_string(),
+            condition,
+            cooldown_secs: 0,
+            hysteresis_percent: 0.0,
+        }
+    }
+
+    pub fn with_cooldown_secs(mut self, cooldown_secs: u64) -> Self {
+        self.cooldown_secs = cooldown_secs;
+        self
+    }
+
+    pub fn with_hysteresis_percent(mut self, hysteresis_percent: f64) -> Self {
+        self.hysteresis_percent = hysteresis_percent;
+        self
+    }
+}
+
+/// A fired rule.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub rule_id: String,
+    pub address: TokenOrPairAddress,
+    /// The observed value (price, ratio or PnL).
+    pub value: f64,
+    /// The measured quantity compared to the threshold (e.g. percent moved).
+    pub metric: f64,
+    pub timestamp: u64,
+    pub message: String,
+}
+
+#[derive(Debug, Default)]
+struct RuleState {
+    // `None` until the first observation.
+    armed: Option<bool>,
+    last_fired: Option<u64>,
+    // (timestamp, value) observations inside the percent-change window.
+    history: VecDeque<(u64, f64)>,
+}
+
+// Which side of the threshold an observation is on.
+enum Reading {
+    // Carries the metric compared to the threshold.
+    Triggered(f64),
+    Cleared,
+    // Inside the hysteresis band: keeps the current state.
+    Band,
+}
+
+fn threshold_reading(value: f64, level: f64, direction: Direction, hysteresis: f64) -> Reading {
+    let margin = level.abs() * hysteresis / 100.0;
+    let (triggered, cleared) = match direction {
+        Direction::Above => (value >= level, value < level - margin),
+        Direction::Below => (value <= level, value > level + margin),
+    };
+    if triggered {
+        Reading::Triggered(value)
+    } else if cleared {
+        Reading::Cleared
+    } else {
+        Reading::Band
+    }
+}
+
+fn magnitude_reading(metric: f64, limit: f64, hysteresis: f64) -> Reading {
+    if metric.abs() >= limit {
+        Reading::Triggered(metric)
+    } else if metric.abs() < limit * (1.0 - hysteresis / 100.0) {
+        Reading::Cleared
+    } else {
+        Reading::Band
+    }
+}
+
+/// Evaluates `AlertRule`s against observed values.
+///
+/// The engine never reads the clock: every observation carries its own timestamp,
+/// so a synthetic series always produces the same alerts.
+#[derive(Debug, Default)]
+pub struct AlertEngine {
+    rules: Vec<AlertRule>,
+    states: HashMap<String, RuleState>,
+}
+
+impl AlertEngine {
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        Self {
+            rules,
+            states: HashMap::new(),
+        }
+    }
+
+    pub fn rules(&self) -> &[AlertRule] {
+        &self.rules
+    }
+
+    /// Adds a rule, replacing (and resetting) any rule with the same id.
+    pub fn add_rule(&mut self, rule: AlertRule) {
+        self.remove_rule(&rule.id);
+        self.rules.push(rule);
+    }
+
+    pub fn remove_rule(&mut self, id: &str) -> Option<AlertRule> {
+        self.states.remove(id);
+        let index = self.rules.iter().position(|rule| rule.id == id)?;
+        Some(self.rules.remove(index))
+    }
+
+    /// Feeds a feeder value (using its `updated_at`); values without a price are ignored.
+    pub fn observe_info(&mut self, address: &str, info: &TokenOrPairPriceInfo) -> Vec<Alert> {
+        let price_info = info.price_info();
+        match price_info.price {
+            Some(value) => self.observe(address, value, price_info.updated_at),
+            None => Vec::new(),
+        }
+    }
+
+    /// Feeds a `PriceFeeder` event; failures produce no alerts.
+    pub fn observe_event(&mut self, event: &FeedEvent) -> Vec<Alert> {
+        match event {
+            FeedEvent::Updated { address, info } => self.observe_info(address, info),
+            FeedEvent::Failed { .. } => Vec::new(),
+        }
+    }
+
+    /// Feeds one observation (`timestamp` in unix seconds) and returns the rules it fired.
+    pub fn observe(&mut self, address: &str, value: f64, timestamp: u64) -> Vec<Alert> {
+        let mut alerts = Vec::new();
+        for rule in &self.rules {
+            if rule.condition.address() != address {
+                continue;
+            }
+            let state = self.states.entry(rule.id.clone()).or_default();
+            if let Some(alert) = evaluate(rule, state, value, timestamp) {
+                alerts.push(alert);
+            }
+        }
+        alerts
+    }
+}
+
+// Updates the percent-change window and returns the move since its oldest value.
+fn windowed_change(state: &mut RuleState, value: f64, timestamp: u64, window_secs: u64) -> f64 {
+    state.history.push_back((timestamp, value));
+    while let Some(&(oldest, _)) = state.history.front() {
+        if timestamp.saturating_sub(oldest) > window_secs {
+            state.history.pop_front();
+        } else {
+            break;
+        }
+    }
+    match state.history.front() {
+        Some(&(_, start)) if start != 0.0 => (value - start) / start * 100.0,
+        _ => 0.0,
+    }
+}
+
+fn describe(condition: &AlertCondition, value: f64, metric: f64) -> String {
+    match condition {
+        AlertCondition::PriceCross {
+            address,
+            level,
+            direction,
+        } => format!("{} crossed {} {} ({})", address, direction, level, value),
+        AlertCondition::PercentChange {
+            address,
+            window_secs,
+            ..
+        } => format!(
+            "{} moved {:+.2}% in {}s ({})",
+            address, metric, window_secs, value
+        ),
+        AlertCondition::PairDeviation {
+            address, expected, ..
+        } => format!(
+            "{} ratio {} is {:+.2}% off {}",
+            address, value, metric, expected
+        ),
+        AlertCondition::PerpPnl {
+            address,
+            threshold_usd,
+            direction,
+        } => format!(
+            "{} PnL {} {} USD ({})",
+            address, direction, threshold_usd, value
+        ),
+    }
+}
+
+fn evaluate(rule: &AlertRule, state: &mut RuleState, value: f64, timestamp: u64) -> Option<Alert> {
+    let hysteresis = rule.hysteresis_percent;
+    let reading = match &rule.condition {
+        AlertCondition::PriceCross {
+            level, direction, ..
+        } => threshold_reading(value, *level, *direction, hysteresis),
+        AlertCondition::PerpPnl {
+            threshold_usd,
+            direction,
+            ..
+        } => threshold_reading(value, *threshold_usd, *direction, hysteresis),
+        AlertCondition::PairDeviation {
+            expected,
+            max_deviation_percent,
+            ..
+        } => magnitude_reading(
+            (value / expected - 1.0) * 100.0,
+            *max_deviation_percent,
+            hysteresis,
+        ),
+        AlertCondition::PercentChange {
+            percent,
+            window_secs,
+            ..
+        } => magnitude_reading(
+            windowed_change(state, value, timestamp, *window_secs),
+            *percent,
+            hysteresis,
+        ),
+    };
+
+    let metric = match reading {
+        Reading::Triggered(metric) => metric,
+        Reading::Cleared => {
+            state.armed = Some(true);
+            return None;
+        }
+        Reading::Band => {
+            state.armed.get_or_insert(true);
+            return None;
+        }
+    };
+
+    // A cross needs an observation on the other side first; other rules fire right away.
+    if state.armed.is_none() && matches!(rule.condition, AlertCondition::PriceCross { .. }) {
+        state.armed = Some(false);
+        return None;
+    }
+    if state.armed == Some(false) {
+        return None;
+    }
+    // Still triggered after the cooldown: fires then.
+    if let Some(last) = state.last_fired {
+        if timestamp.saturating_sub(last) < rule.cooldown_secs {
+            state.armed = Some(true);
+            return None;
+        }
+    }
+
+    state.armed = Some(false);
+    state.last_fired = Some(timestamp);
+    Some(Alert {
+        rule_id: rule.id.clone(),
+        address: rule.condition.address().to_string(),
+        value,
+        metric,
+        timestamp,
+        message: describe(&rule.condition, value, metric),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::feeder::{PriceInfo, TokenPriceInfo};
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    // Feeds `(timestamp, value)` pairs and returns the timestamps that fired.
+    fn run(engine: &mut AlertEngine, address: &str, series: &[(u64, f64)]) -> Vec<u64> {
+        series
+            .iter()
+            .flat_map(|&(t, v)| engine.observe(address, v, t))
+            .map(|alert| alert.timestamp)
+            .collect()
+    }
+
+    #[test]
+    fn test_price_cross_with_hysteresis() {
+        let rule = AlertRule::new(
+            "sol-150",
+            AlertCondition::PriceCross {
+                address: SOL.to_string(),
+                level: 150.0,
+                direction: Direction::Above,
+            },
+        )
+        .with_hysteresis_percent(1.0);
+        let mut engine = AlertEngine::new(vec![rule]);
+
+        let series = [
+            (0, 148.0),
+            (1, 151.0), // crosses
+            (2, 152.0),
+            (3, 149.5), // inside the 1% band: stays disarmed
+            (4, 151.0),
+            (5, 148.0), // clears
+            (6, 150.0), // crosses again
+        ];
+        assert_eq!(run(&mut engine, SOL, &series), vec![1, 6]);
+
+        // Already above the level on the first observation: not a cross.
+        let mut engine = AlertEngine::new(engine.rules().to_vec());
+        assert!(run(&mut engine, SOL, &[(0, 155.0), (1, 156.0)]).is_empty());
+    }
+
+    #[test]
+    fn test_perp_pnl_cooldown() {
+        let address = "SOL_PERPS_wallet";
+        let rule = AlertRule::new(
+            "pnl-100",
+            AlertCondition::PerpPnl {
+                address: address.to_string(),
+                threshold_usd: 100.0,
+                direction: Direction::Above,
+            },
+        )
+        .with_cooldown_secs(60);
+        let mut engine = AlertEngine::new(vec![rule]);
+
+        let series = [
+            (0, 120.0),
+            (10, 90.0),
+            (20, 120.0),
+            (70, 120.0),
+            (80, 130.0),
+        ];
+        assert_eq!(run(&mut engine, address, &series), vec![0, 70]);
+    }
+
+    #[test]
+    fn test_percent_change_over_window() {
+        let rule = AlertRule::new(
+            "sol-5pct",
+            AlertCondition::PercentChange {
+                address: SOL.to_string(),
+                percent: 5.0,
+                window_secs: 60,
+            },
+        );
+        let mut engine = AlertEngine::new(vec![rule]);
+
+        let alerts: Vec<Alert> = [
+            (0, 100.0),
+            (30, 103.0),
+            (50, 106.0),
+            (200, 107.0),
+            (230, 100.5),
+        ]
+        .iter()
+        .flat_map(|&(t, v)| engine.observe(SOL, v, t))
+        .collect();
+
+        assert_eq!(alerts.len(), 2);
+        assert_eq!(alerts[0].timestamp, 50);
+        assert!((alerts[0].metric - 6.0).abs() < 1e-9);
+        assert_eq!(alerts[1].timestamp, 230);
+        assert!(alerts[1].metric < -5.0);
+    }
+
+    #[test]
+    fn test_pair_deviation() {
+        let pair = "jupSoLaHXQiZZTSfEWMTRRgpnyFm8f6sZdosWBjx93v_So11111111111111111111111111111111111111112";
+        let rule = AlertRule::new(
+            "jupsol-depeg",
+            AlertCondition::PairDeviation {
+                address: pair.to_string(),
+                expected: 1.1,
+                max_deviation_percent: 2.0,
+            },
+        );
+        let mut engine = AlertEngine::new(vec![rule]);
+
+        let alerts: Vec<Alert> = [(0, 1.1), (1, 1.09), (2, 1.07), (3, 1.06)]
+            .iter()
+            .flat_map(|&(t, v)| engine.observe(pair, v, t))
+            .collect();
+
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].timestamp, 2);
+        assert!(alerts[0].metric < -2.0);
+        assert!(alerts[0].message.contains("off 1.1"));
+    }
+
+    #[test]
+    fn test_rules_roundtrip_through_json() {
+        let json = r#"[
+            {"id": "a", "condition": {"kind": "price_cross", "address": "SOL", "level": 150.0, "direction": "below"}},
+            {"id": "b", "condition": {"kind": "percent_change", "address": "SOL", "percent": 5.0, "window_secs": 3600}, "cooldown_secs": 600}
+        ]"#;
+        let rules: Vec<AlertRule> = serde_json::from_str(json).unwrap();
+
+        assert_eq!(rules[0].hysteresis_percent, 0.0);
+        assert_eq!(rules[1].cooldown_secs, 600);
+        let encoded = serde_json::to_string(&rules).unwrap();
+        assert_eq!(
+            serde_json::from_str::<Vec<AlertRule>>(&encoded).unwrap(),
+            rules
+        );
+    }
+
+    #[test]
+    fn test_observe_feed_event() {
+        let rule = AlertRule::new(
+            "sol-below-100",
+            AlertCondition::PriceCross {
+                address: SOL.to_string(),
+                level: 100.0,
+                direction: Direction::Below,
+            },
+        );
+        let mut engine = AlertEngine::new(vec![rule]);
+        let event = |price: f64, updated_at: u64| FeedEvent::Updated {
+            address: SOL.to_string(),
+            info: Box::new(TokenOrPairPriceInfo::Token(TokenPriceInfo {
+                price_info: PriceInfo {
+                    price: Some(price),
+                    ui_price: price.to_string(),
+                    updated_at,
+                },
+                ..Default::default()
+            })),
+        };
+
+        assert!(engine.observe_event(&event(101.0, 1)).is_empty());
+        let alerts = engine.observe_event(&event(99.0, 2));
+        assert_eq!(alerts.len(), 1);
+        assert_eq!(alerts[0].timestamp, 2);
+    }
+}
@@ -0,0 +1,160 @@
+use crate::error::{parse_f64, Result};
+use crate::perps::{PerpsFetcher, PoolInfo, PositionData, Side};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::time::Duration;
+
+const SECS_PER_HOUR: f64 = 60.0 * 60.0;
+
+/// Projected borrow fee accrual of an open position at the current pool borrow rate.
+///
+/// Jupiter charges borrow fees hourly on the position size; the rate follows
+/// custody utilization, so projections only hold while utilization stays put.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BorrowProjection {
+    pub position_pubkey: String,
+    pub side: Side,
+    pub market_mint: String,
+    pub size: f64,
+    pub collateral: f64,
+    /// Borrow fees accrued so far (USD).
+    pub accrued_usd: f64,
+    /// Hourly borrow rate in percent of size.
+    pub borrow_rate_percent: f64,
+    pub utilization_percent: f64,
+    pub hourly_usd: f64,
+    pub projected_24h_usd: f64,
+    pub projected_7d_usd: f64,
+}
+
+impl BorrowProjection {
+    /// Projects `position` using the rates of its market's `pool`.
+    pub fn new(position: &PositionData, pool: &PoolInfo) -> Result<Self> {
+        let id = &position.position_pubkey;
+        let size = parse_f64("size", &position.size, id)?;
+        let borrow_rate_percent =
+            pool.borrow_rate_percent(&position.side, &position.market_mint)?;
+        let hourly_usd = size * borrow_rate_percent / 100.0;
+
+        Ok(Self {
+            position_pubkey: position.position_pubkey.clone(),
+            side: position.side.clone(),
+            market_mint: position.market_mint.clone(),
+            size,
+            collateral: parse_f64("collateral", &position.collateral, id)?,
+            accrued_usd: parse_f64("borrow_fees_usd", &position.borrow_fees_usd, id)?,
+            borrow_rate_percent,
+            utilization_percent: pool.utilization_percent(&position.side, &position.market_mint)?,
+            hourly_usd,
+            projected_24h_usd: hourly_usd * 24.0,
+            projected_7d_usd: hourly_usd * 24.0 * 7.0,
+        })
+    }
+
+    /// Fees accruing over `duration` from now.
+    pub fn projected_usd(&self, duration: Duration) -> f64 {
+        self.hourly_usd * duration.as_secs_f64() / SECS_PER_HOUR
+    }
+
+    /// Time until total borrow fees (accrued plus projected) reach `fraction` of the
+    /// collateral, e.g. `0.1` for 10%. Zero if they already have; `None` if nothing accrues
+    /// or the time is too long (or not a number) to represent.
+    pub fn time_until_collateral_fraction(&self, fraction: f64) -> Option<Duration> {
+        let remaining = self.collateral * fraction - self.accrued_usd;
+        if remaining <= 0.0 {
+            return Some(Duration::ZERO);
+        }
+        if self.hourly_usd <= 0.0 {
+            return None;
+        }
+        Duration::try_from_secs_f64(remaining / self.hourly_usd * SECS_PER_HOUR).ok()
+    }
+}
+
+impl PerpsFetcher {
+    /// Fetches a wallet's positions and the pool info of each market traded,
+    /// and projects borrow fees per position.
+    pub async fn fetch_borrow_projections(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Vec<BorrowProjection>> {
+        let positions = self.fetch_positions(wallet_address).await?.data_list;
+
+        let mut pools: HashMap<&str, PoolInfo> = HashMap::new();
+        for position in &positions {
+            let mint = position.market_mint.as_str();
+            if !pools.contains_key(mint) {
+                pools.insert(mint, self.fetch_pool_info(mint).await?);
+            }
+        }
+
+        positions
+            .iter()
+            .map(|position| BorrowProjection::new(position, &pools[position.market_mint.as_str()]))
+            .collect()
+    }
+}
+
+#[cfg(all(feature = "log-native", feature = "native"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perps::{
+        mock_positions_fetcher, MOCK_WALLET, PERPS_API_BASE, POOL_INFO_FIXTURE, POSITIONS_FIXTURE,
+    };
+    use crate::transport::MockResponse;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[tokio::test]
+    async fn test_fetch_borrow_projections() -> Result<()> {
+        let pool_url = format!("{}/pool-info?mint={}", PERPS_API_BASE, SOL);
+        let positions: serde_json::Value = serde_json::from_str(POSITIONS_FIXTURE).unwrap();
+        let pool: serde_json::Value = serde_json::from_str(POOL_INFO_FIXTURE).unwrap();
+        let (mock, perps_fetcher) = mock_positions_fetcher(MOCK_WALLET, &positions);
+        mock.push(&pool_url, MockResponse::json(200, &pool));
+
+        let projections = perps_fetcher.fetch_borrow_projections(MOCK_WALLET).await?;
+
+        // Both positions trade SOL, so the pool is fetched once.
+        assert_eq!(mock.request_count(&pool_url), 1);
+
+        // Long: $1000 at 0.0012%/h, $0.50 accrued on $100 collateral.
+        let long = &projections[0];
+        assert!((long.hourly_usd - 0.012).abs() < 1e-9);
+        assert!((long.projected_24h_usd - 0.288).abs() < 1e-9);
+        assert!((long.projected_7d_usd - 2.016).abs() < 1e-9);
+        assert!((long.projected_usd(Duration::from_secs(30 * 60)) - 0.006).abs() < 1e-9);
+        assert_eq!(
+            long.time_until_collateral_fraction(0.01)
+                .unwrap()
+                .as_secs_f64()
+                .round(),
+            150_000.0
+        );
+        assert_eq!(
+            long.time_until_collateral_fraction(0.005),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(long.time_until_collateral_fraction(f64::NAN), None);
+        let barely_accruing = BorrowProjection {
+            hourly_usd: 1e-300,
+            ..long.clone()
+        };
+        assert_eq!(barely_accruing.time_until_collateral_fraction(0.5), None);
+
+        // Short: $500 at 0.0025%/h, $0.20 accrued on $50 collateral.
+        let short = &projections[1];
+        assert_eq!(short.utilization_percent, 63.8);
+        assert!((short.projected_24h_usd - 0.3).abs() < 1e-9);
+        assert_eq!(
+            short
+                .time_until_collateral_fraction(0.1)
+                .unwrap()
+                .as_secs_f64()
+                .round(),
+            384.0 * 3600.0
+        );
+        Ok(())
+    }
+}
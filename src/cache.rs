@@ -0,0 +1,551 @@
+use crate::compat::{self, BoxFuture};
+use crate::error::Result;
+use crate::fetcher::Fetcher;
+use crate::transport::TransportRequest;
+use futures_channel::oneshot;
+use futures_util::future::{FutureExt, Shared};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// A successful response body and when it was stored (`compat::now_millis`).
+#[derive(Debug, Clone)]
+pub struct CachedResponse {
+    pub body: Vec<u8>,
+    pub stored_at_ms: f64,
+}
+
+impl CachedResponse {
+    pub fn new(body: Vec<u8>) -> Self {
+        Self {
+            body,
+            stored_at_ms: compat::now_millis(),
+        }
+    }
+
+    pub fn age(&self, now_ms: f64) -> Duration {
+        Duration::from_secs_f64((now_ms - self.stored_at_ms).max(0.0) / 1000.0)
+    }
+}
+
+/// Storage for cached GET responses, keyed by URL.
+pub trait CacheBackend: Send + Sync {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>>;
+
+    /// Stores `entry`; the backend may drop it once `keep_for` has passed.
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+        keep_for: Duration,
+    ) -> BoxFuture<'a, ()>;
+}
+
+/// How long responses stay fresh, optionally per URL prefix.
+#[derive(Debug, Clone)]
+pub struct CachePolicy {
+    pub default_ttl: Duration,
+    /// How long an expired entry may still be served while it is refreshed in the background.
+    pub stale_while_revalidate: Duration,
+    ttl_rules: Vec<(String, Duration)>,
+}
+
+impl Default for CachePolicy {
+    fn default() -> Self {
+        Self {
+            default_ttl: Duration::from_secs(5),
+            stale_while_revalidate: Duration::ZERO,
+            ttl_rules: Vec::new(),
+        }
+    }
+}
+
+impl CachePolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_default_ttl(mut self, ttl: Duration) -> Self {
+        self.default_ttl = ttl;
+        self
+    }
+
+    pub fn with_stale_while_revalidate(mut self, window: Duration) -> Self {
+        self.stale_while_revalidate = window;
+        self
+    }
+
+    /// TTL for URLs starting with `prefix`; the longest matching prefix wins.
+    /// A zero TTL disables caching for those URLs.
+    pub fn with_ttl_for(mut self, prefix: &str, ttl: Duration) -> Self {
+        self.ttl_rules.retain(|(existing, _)| existing != prefix);
+        self.ttl_rules.push((prefix.to_string(), ttl));
+        self
+    }
+
+    pub fn ttl_for(&self, url: &str) -> Duration {
+        self.ttl_rules
+            .iter()
+            .filter(|(prefix, _)| url.starts_with(prefix.as_str()))
+            .max_by_key(|(prefix, _)| prefix.len())
+            .map_or(self.default_ttl, |(_, ttl)| *ttl)
+    }
+}
+
+#[derive(Debug)]
+struct MemoryEntry {
+    response: CachedResponse,
+    expires_at_ms: f64,
+    last_used: u64,
+}
+
+#[derive(Debug, Default)]
+struct MemoryState {
+    entries: HashMap<String, MemoryEntry>,
+    tick: u64,
+}
+
+/// In-process cache holding at most `max_entries` responses (least recently used are evicted).
+#[derive(Debug)]
+pub struct MemoryCache {
+    max_entries: usize,
+    state: Mutex<MemoryState>,
+}
+
+impl MemoryCache {
+    pub fn new(max_entries: usize) -> Self {
+        Self {
+            max_entries,
+            state: Mutex::new(MemoryState::default()),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.state.lock().unwrap().entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl Default for MemoryCache {
+    fn default() -> Self {
+        Self::new(1024)
+    }
+}
+
+impl CacheBackend for MemoryCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>> {
+        Box::pin(async move {
+            let now_ms = compat::now_millis();
+            let mut state = self.state.lock().unwrap();
+            state.tick += 1;
+            let tick = state.tick;
+            match state.entries.get_mut(key) {
+                Some(entry) if entry.expires_at_ms > now_ms => {
+                    entry.last_used = tick;
+                    Some(entry.response.clone())
+                }
+                Some(_) => {
+                    state.entries.remove(key);
+                    None
+                }
+                None => None,
+            }
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+        keep_for: Duration,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            if self.max_entries == 0 {
+                return;
+            }
+            let now_ms = compat::now_millis();
+            let mut state = self.state.lock().unwrap();
+            state.tick += 1;
+            let tick = state.tick;
+
+            if !state.entries.contains_key(key) && state.entries.len() >= self.max_entries {
+                state.entries.retain(|_, e| e.expires_at_ms > now_ms);
+                if state.entries.len() >= self.max_entries {
+                    let oldest = state
+                        .entries
+                        .iter()
+                        .min_by_key(|(_, e)| e.last_used)
+                        .map(|(k, _)| k.clone());
+                    if let Some(oldest) = oldest {
+                        state.entries.remove(&oldest);
+                    }
+                }
+            }
+
+            let expires_at_ms = entry.stored_at_ms + keep_for.as_secs_f64() * 1000.0;
+            state.entries.insert(
+                key.to_string(),
+                MemoryEntry {
+                    response: entry,
+                    expires_at_ms,
+                    last_used: tick,
+                },
+            );
+        })
+    }
+}
+
+#[cfg(feature = "worker")]
+const STORED_AT_HEADER: &str = "x-jup-sdk-stored-at";
+
+/// Backend on the Cloudflare Workers Cache API (per data center, shared between isolates).
+#[cfg(feature = "worker")]
+#[derive(Debug, Clone, Default)]
+pub struct WorkerCache {
+    // `None` uses `caches.default`.
+    name: Option<String>,
+}
+
+#[cfg(feature = "worker")]
+impl WorkerCache {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses a named cache (`caches.open(name)`) instead of the default one.
+    pub fn named(name: &str) -> Self {
+        Self {
+            name: Some(name.to_string()),
+        }
+    }
+
+    // `worker::Cache` wraps a JS object and is not `Send`, so it is opened per call.
+    async fn open(&self) -> worker::Cache {
+        match &self.name {
+            Some(name) => worker::Cache::open(name.clone()).await,
+            None => worker::Cache::default(),
+        }
+    }
+}
+
+#[cfg(feature = "worker")]
+impl CacheBackend for WorkerCache {
+    fn get<'a>(&'a self, key: &'a str) -> BoxFuture<'a, Option<CachedResponse>> {
+        Box::pin(async move {
+            let cache = self.open().await;
+            let mut response = cache.get(key, false).await.ok()??;
+            let stored_at_ms = response
+                .headers()
+                .get(STORED_AT_HEADER)
+                .ok()??
+                .parse::<f64>()
+                .ok()?;
+            let body = response.bytes().await.ok()?;
+            Some(CachedResponse { body, stored_at_ms })
+        })
+    }
+
+    fn put<'a>(
+        &'a self,
+        key: &'a str,
+        entry: CachedResponse,
+        keep_for: Duration,
+    ) -> BoxFuture<'a, ()> {
+        Box::pin(async move {
+            let mut headers = worker::Headers::new();
+            let _ = headers.set("cache-control", &format!("max-age={}", keep_for.as_secs()));
+            let _ = headers.set(STORED_AT_HEADER, &entry.stored_at_ms.to_string());
+            let Ok(response) = worker::Response::from_bytes(entry.body) else {
+                return;
+            };
+            let cache = self.open().await;
+            if let Err(e) = cache.put(key, response.with_headers(headers)).await {
+                crate::platform_log!(warn, "Failed to cache response for {}: {}", key, e);
+            }
+        })
+    }
+}
+
+type InFlight = Shared<oneshot::Receiver<Option<Arc<Vec<u8>>>>>;
+
+// Held by the request that performs the fetch; followers await `InFlight`.
+// Dropping it (on completion or cancellation) lets the next request lead again.
+struct Flight<'a> {
+    cache: &'a ResponseCache,
+    key: String,
+    sender: Option<oneshot::Sender<Option<Arc<Vec<u8>>>>>,
+}
+
+impl Flight<'_> {
+    fn finish(mut self, body: Option<&Vec<u8>>) {
+        if let Some(sender) = self.sender.take() {
+            let _ = sender.send(body.map(|body| Arc::new(body.clone())));
+        }
+    }
+}
+
+impl Drop for Flight<'_> {
+    fn drop(&mut self) {
+        self.cache.in_flight.lock().unwrap().remove(&self.key);
+    }
+}
+
+enum Role<'a> {
+    Leader(Flight<'a>),
+    Follower(InFlight),
+}
+
+/// Caches successful GET responses in front of a `Fetcher` (see `Fetcher::with_cache`).
+///
+/// Concurrent requests for the same URL share one in-flight fetch. Entries older than
+/// their TTL but within `stale_while_revalidate` are served immediately while a
+/// background refresh runs.
+pub struct ResponseCache {
+    backend: Arc<dyn CacheBackend>,
+    policy: CachePolicy,
+    in_flight: Mutex<HashMap<String, InFlight>>,
+}
+
+impl ResponseCache {
+    pub fn new(backend: Arc<dyn CacheBackend>, policy: CachePolicy) -> Self {
+        Self {
+            backend,
+            policy,
+            in_flight: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// A `MemoryCache`-backed cache.
+    pub fn in_memory(max_entries: usize, policy: CachePolicy) -> Self {
+        Self::new(Arc::new(MemoryCache::new(max_entries)), policy)
+    }
+
+    pub fn policy(&self) -> &CachePolicy {
+        &self.policy
+    }
+
+    pub(crate) async fn get_or_fetch(
+        self: &Arc<Self>,
+        fetcher: &Fetcher,
+        request: TransportRequest,
+    ) -> Result<Vec<u8>> {
+        let ttl = self.policy.ttl_for(&request.url);
+        if ttl.is_zero() {
+            return fetcher.send_with_retry(request).await;
+        }
+
+        if let Some(entry) = self.backend.get(&request.url).await {
+            let age = entry.age(compat::now_millis());
+            if age < ttl {
+                return Ok(entry.body);
+            }
+            if age < ttl + self.policy.stale_while_revalidate {
+                self.revalidate_in_background(fetcher.clone(), request);
+                return Ok(entry.body);
+            }
+        }
+
+        match self.join(&request.url) {
+            Role::Leader(flight) => self.lead(fetcher, request, flight).await,
+            Role::Follower(in_flight) => match in_flight.await {
+                Ok(Some(body)) => Ok(body.as_ref().clone()),
+                // The leader failed or was cancelled: fetch directly so this caller
+                // gets its own typed error (or a late success).
+                _ => fetcher.send_with_retry(request).await,
+            },
+        }
+    }
+
+    fn join(&self, key: &str) -> Role<'_> {
+        let mut in_flight = self.in_flight.lock().unwrap();
+        if let Some(existing) = in_flight.get(key) {
+            return Role::Follower(existing.clone());
+        }
+        let (sender, receiver) = oneshot::channel();
+        in_flight.insert(key.to_string(), receiver.shared());
+        Role::Leader(Flight {
+            cache: self,
+            key: key.to_string(),
+            sender: Some(sender),
+        })
+    }
+
+    async fn lead(
+        &self,
+        fetcher: &Fetcher,
+        request: TransportRequest,
+        flight: Flight<'_>,
+    ) -> Result<Vec<u8>> {
+        let key = request.url.clone();
+        let result = fetcher.send_with_retry(request).await;
+        if let Ok(body) = &result {
+            let keep_for = self.policy.ttl_for(&key) + self.policy.stale_while_revalidate;
+            self.backend
+                .put(&key, CachedResponse::new(body.clone()), keep_for)
+                .await;
+        }
+        flight.finish(result.as_ref().ok());
+        result
+    }
+
+    fn revalidate_in_background(self: &Arc<Self>, fetcher: Fetcher, request: TransportRequest) {
+        let cache = Arc::clone(self);
+        compat::spawn(async move {
+            // Another caller is already refreshing this URL.
+            let Role::Leader(flight) = cache.join(&request.url) else {
+                return;
+            };
+            if let Err(e) = cache.lead(&fetcher, request, flight).await {
+                crate::platform_log!(warn, "Background cache refresh failed: {}", e);
+            }
+        });
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::transport::{MockResponse, MockTransport};
+    use serde_json::json;
+
+    const URL: &str = "https://api.jup.ag/price/v2?ids=SOL";
+
+    fn cached_fetcher(mock: Arc<MockTransport>, policy: CachePolicy) -> Fetcher {
+        Fetcher::new()
+            .with_transport(mock)
+            .with_cache(Arc::new(ResponseCache::in_memory(16, policy)))
+    }
+
+    #[test]
+    fn test_policy_longest_prefix_wins() {
+        let policy = CachePolicy::new()
+            .with_default_ttl(Duration::from_secs(5))
+            .with_ttl_for("https://api-v3.raydium.io", Duration::from_secs(60))
+            .with_ttl_for("https://api-v3.raydium.io/pools", Duration::from_secs(30));
+
+        assert_eq!(policy.ttl_for(URL), Duration::from_secs(5));
+        assert_eq!(
+            policy.ttl_for("https://api-v3.raydium.io/pools/info/ids?ids=x"),
+            Duration::from_secs(30)
+        );
+        assert_eq!(
+            policy.ttl_for("https://api-v3.raydium.io/mint/list"),
+            Duration::from_secs(60)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_memory_cache_evicts_least_recently_used() {
+        let cache = MemoryCache::new(2);
+        let keep = Duration::from_secs(60);
+        cache
+            .put("a", CachedResponse::new(b"a".to_vec()), keep)
+            .await;
+        cache
+            .put("b", CachedResponse::new(b"b".to_vec()), keep)
+            .await;
+        assert!(cache.get("a").await.is_some());
+        cache
+            .put("c", CachedResponse::new(b"c".to_vec()), keep)
+            .await;
+
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get("a").await.is_some());
+        assert!(cache.get("b").await.is_none());
+        assert!(cache.get("c").await.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_fresh_entry_is_served_from_cache() {
+        let mock = Arc::new(MockTransport::new().on(URL, MockResponse::json(200, &json!(1))));
+        let fetcher = cached_fetcher(mock.clone(), CachePolicy::new());
+
+        for _ in 0..3 {
+            let value: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+            assert_eq!(value, 1);
+        }
+        assert_eq!(mock.request_count(URL), 1);
+    }
+
+    #[tokio::test]
+    async fn test_expired_entry_is_refetched() {
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(URL, MockResponse::json(200, &json!(1)))
+                .on(URL, MockResponse::json(200, &json!(2))),
+        );
+        let policy = CachePolicy::new().with_default_ttl(Duration::from_millis(20));
+        let fetcher = cached_fetcher(mock.clone(), policy);
+
+        let first: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+        compat::sleep(Duration::from_millis(40)).await;
+        let second: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+
+        assert_eq!((first, second), (1, 2));
+        assert_eq!(mock.request_count(URL), 2);
+    }
+
+    #[tokio::test]
+    async fn test_stale_entry_is_served_while_revalidating() {
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(URL, MockResponse::json(200, &json!(1)))
+                .on(URL, MockResponse::json(200, &json!(2))),
+        );
+        let policy = CachePolicy::new()
+            .with_default_ttl(Duration::from_millis(20))
+            .with_stale_while_revalidate(Duration::from_secs(60));
+        let fetcher = cached_fetcher(mock.clone(), policy);
+
+        let _: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+        compat::sleep(Duration::from_millis(40)).await;
+
+        // Stale value comes back immediately; the refresh happens in the background.
+        let stale: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+        assert_eq!(stale, 1);
+        compat::sleep(Duration::from_millis(20)).await;
+
+        let refreshed: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+        assert_eq!(refreshed, 2);
+        assert_eq!(mock.request_count(URL), 2);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_requests_share_one_fetch() {
+        let mock = Arc::new(MockTransport::new().on(
+            URL,
+            MockResponse::json(200, &json!(7)).with_delay(Duration::from_millis(50)),
+        ));
+        let fetcher = cached_fetcher(mock.clone(), CachePolicy::new());
+
+        let (a, b, c) = tokio::join!(
+            fetcher.fetch_with_retry::<u32>(URL),
+            fetcher.fetch_with_retry::<u32>(URL),
+            fetcher.fetch_with_retry::<u32>(URL),
+        );
+
+        assert_eq!((a.unwrap(), b.unwrap(), c.unwrap()), (7, 7, 7));
+        assert_eq!(mock.request_count(URL), 1);
+    }
+
+    #[tokio::test]
+    async fn test_errors_and_posts_are_not_cached() {
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(URL, MockResponse::text(404, "missing"))
+                .on(URL, MockResponse::json(200, &json!(3))),
+        );
+        let fetcher = cached_fetcher(mock.clone(), CachePolicy::new());
+
+        assert!(fetcher.fetch_with_retry::<u32>(URL).await.is_err());
+        let value: u32 = fetcher.fetch_with_retry(URL).await.unwrap();
+        assert_eq!(value, 3);
+
+        let _: u32 = fetcher.post_with_retry(URL, &json!({})).await.unwrap();
+        let _: u32 = fetcher.post_with_retry(URL, &json!({})).await.unwrap();
+        assert_eq!(mock.request_count(URL), 4);
+    }
+}
@@ -1,7 +1,32 @@
-use anyhow::{anyhow, Result};
+use crate::error::{JupSdkError, Result};
 use std::future::Future;
+use std::pin::Pin;
 use std::time::Duration;
 
+// --- Boxed futures ---
+
+#[cfg(not(feature = "worker"))]
+/// Boxed future used by object-safe async traits (`Send` on native).
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + Send + 'a>>;
+
+#[cfg(feature = "worker")]
+/// Boxed future used by object-safe async traits (no `Send` in Workers).
+pub type BoxFuture<'a, T> = Pin<Box<dyn Future<Output = T> + 'a>>;
+
+// --- Thread-safety bounds ---
+
+#[cfg(not(feature = "worker"))]
+/// `Send + Sync` on native; nothing in Workers, where JS handles are neither.
+pub trait MaybeSendSync: Send + Sync {}
+#[cfg(not(feature = "worker"))]
+impl<T: Send + Sync + ?Sized> MaybeSendSync for T {}
+
+#[cfg(feature = "worker")]
+/// `Send + Sync` on native; nothing in Workers, where JS handles are neither.
+pub trait MaybeSendSync {}
+#[cfg(feature = "worker")]
+impl<T: ?Sized> MaybeSendSync for T {}
+
 // --- Sleep ---
 
 #[cfg(not(feature = "worker"))]
@@ -18,6 +43,44 @@ pub async fn sleep(duration: Duration) {
     worker::Delay::from(duration).await;
 }
 
+// --- Background tasks ---
+
+#[cfg(not(feature = "worker"))]
+/// Runs a future in the background on the current tokio runtime.
+pub fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    tokio::spawn(future);
+}
+
+#[cfg(feature = "worker")]
+/// Runs a future in the background on the Workers event loop.
+pub fn spawn<F>(future: F)
+where
+    F: Future<Output = ()> + 'static,
+{
+    wasm_bindgen_futures::spawn_local(future);
+}
+
+// --- Clock ---
+
+#[cfg(not(feature = "worker"))]
+/// Milliseconds on a monotonic clock, for measuring intervals.
+pub fn now_millis() -> f64 {
+    use once_cell::sync::Lazy;
+    use std::time::Instant;
+
+    static START: Lazy<Instant> = Lazy::new(Instant::now);
+    START.elapsed().as_secs_f64() * 1000.0
+}
+
+#[cfg(feature = "worker")]
+/// Milliseconds since the epoch from the JS clock (`Instant` is unavailable in Workers).
+pub fn now_millis() -> f64 {
+    js_sys::Date::now()
+}
+
 // --- Timeout ---
 
 #[cfg(not(feature = "worker"))]
@@ -41,7 +104,7 @@ where
         }
         _ = sleep(duration) => {
             // The delay completed first
-            Err(anyhow!("Operation timed out after {:?}", duration))
+            Err(JupSdkError::Timeout { after: duration })
         }
     }
 }
@@ -68,7 +131,7 @@ where
         }
         Either::Right((_, _)) => {
             // The delay completed first
-            Err(anyhow!("Operation timed out after {:?}", duration))
+            Err(JupSdkError::Timeout { after: duration })
         }
     }
 }
@@ -0,0 +1,195 @@
+use crate::transport::TransportError;
+use std::fmt;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NotFoundKind {
+    Token,
+    BaseToken,
+    Pool,
+}
+
+impl fmt::Display for NotFoundKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotFoundKind::Token => write!(f, "Token"),
+            NotFoundKind::BaseToken => write!(f, "Base token"),
+            NotFoundKind::Pool => write!(f, "Pool"),
+        }
+    }
+}
+
+/// Errors returned by the SDK fetchers.
+#[derive(Debug)]
+pub enum JupSdkError {
+    /// A single attempt took longer than `RetrySettings::request_timeout`.
+    Timeout { after: Duration },
+    /// The transport failed before an HTTP response was received.
+    Transport { url: String, source: TransportError },
+    /// The server answered with a non-success status.
+    HttpStatus {
+        url: String,
+        status: u16,
+        body: String,
+    },
+    /// The request body could not be encoded as JSON.
+    Serialize {
+        url: String,
+        source: serde_json::Error,
+    },
+    /// The response body did not match the expected type.
+    Deserialize {
+        url: String,
+        source: serde_json::Error,
+    },
+    /// The response did not contain the requested token/pool.
+    NotFound { kind: NotFoundKind, id: String },
+    /// A numeric string field could not be parsed.
+    Parse {
+        field: String,
+        value: String,
+        id: String,
+        reason: String,
+    },
+    /// A storage backend failed to read or write records.
+    Storage { operation: String, reason: String },
+    /// A UI amount has no base-unit equivalent (negative, not finite or too large).
+    InvalidAmount {
+        amount: f64,
+        decimals: u8,
+        reason: String,
+    },
+    /// Every attempt failed with a retryable error; `source` is the last one.
+    RetriesExhausted {
+        url: String,
+        attempts: usize,
+        source: Box<JupSdkError>,
+    },
+}
+
+pub type Result<T> = std::result::Result<T, JupSdkError>;
+
+impl JupSdkError {
+    pub fn not_found(kind: NotFoundKind, id: impl Into<String>) -> Self {
+        JupSdkError::NotFound {
+            kind,
+            id: id.into(),
+        }
+    }
+
+    pub fn storage(operation: &str, reason: impl fmt::Display) -> Self {
+        JupSdkError::Storage {
+            operation: operation.to_string(),
+            reason: reason.to_string(),
+        }
+    }
+
+    /// Whether another attempt of the same request may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            JupSdkError::Timeout { .. } => true,
+            JupSdkError::Transport { source, .. } => source.is_retryable(),
+            _ => false,
+        }
+    }
+}
+
+impl fmt::Display for JupSdkError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JupSdkError::Timeout { after } => write!(f, "Operation timed out after {:?}", after),
+            JupSdkError::Transport { url, source } => {
+                write!(f, "Request to {} failed: {}", url, source)
+            }
+            JupSdkError::HttpStatus { url, status, body } => write!(
+                f,
+                "Request to {} failed: Status {}, Body: {}",
+                url, status, body
+            ),
+            JupSdkError::Serialize { url, source } => {
+                write!(
+                    f,
+                    "Failed to serialize request body for {}: {}",
+                    url, source
+                )
+            }
+            JupSdkError::Deserialize { url, source } => {
+                write!(f, "Failed to deserialize response from {}: {}", url, source)
+            }
+            JupSdkError::NotFound { kind, id } => write!(f, "{} {} not found", kind, id),
+            JupSdkError::Parse {
+                field,
+                value,
+                id,
+                reason,
+            } => write!(
+                f,
+                "Failed to parse {} '{}' for {}: {}",
+                field, value, id, reason
+            ),
+            JupSdkError::Storage { operation, reason } => {
+                write!(f, "Storage {} failed: {}", operation, reason)
+            }
+            JupSdkError::InvalidAmount {
+                amount,
+                decimals,
+                reason,
+            } => write!(
+                f,
+                "Invalid amount {} with {} decimals: {}",
+                amount, decimals, reason
+            ),
+            JupSdkError::RetriesExhausted {
+                url,
+                attempts,
+                source,
+            } => write!(
+                f,
+                "Request to {} failed after {} attempts: {}",
+                url, attempts, source
+            ),
+        }
+    }
+}
+
+impl std::error::Error for JupSdkError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JupSdkError::Transport { source, .. } => Some(source),
+            JupSdkError::Serialize { source, .. } => Some(source),
+            JupSdkError::Deserialize { source, .. } => Some(source),
+            JupSdkError::RetriesExhausted { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a numeric string field, naming the field and its owner on failure.
+/// `NaN` and infinities are rejected.
+pub(crate) fn parse_f64(field: &str, value: &str, id: &str) -> Result<f64> {
+    let error = |reason: String| JupSdkError::Parse {
+        field: field.to_string(),
+        value: value.to_string(),
+        id: id.to_string(),
+        reason,
+    };
+    match value.parse::<f64>() {
+        Ok(number) if number.is_finite() => Ok(number),
+        Ok(_) => Err(error("not a finite number".to_string())),
+        Err(e) => Err(error(e.to_string())),
+    }
+}
+
+/// Like `parse_f64`, but exact; accepts plain and scientific notation.
+pub(crate) fn parse_decimal(field: &str, value: &str, id: &str) -> Result<rust_decimal::Decimal> {
+    use std::str::FromStr;
+
+    rust_decimal::Decimal::from_str(value)
+        .or_else(|_| rust_decimal::Decimal::from_scientific(value))
+        .map_err(|e| JupSdkError::Parse {
+            field: field.to_string(),
+            value: value.to_string(),
+            id: id.to_string(),
+            reason: e.to_string(),
+        })
+}
@@ -1,9 +1,21 @@
 use crate::{
-    ray::{fetch_pool_info_by_id, PoolId}, // This function now uses Fetcher internally
-    token_registry::Token,
+    compat,
+    error::{JupSdkError, Result},
+    formatter::format_price,
+    perps::PerpsFetcher,
+    prices::PriceFetcher,
+    ray::{PoolId, RaydiumFetcher},
+    time::get_unix_timestamp,
+    token_registry::{self, Token},
 };
+use futures_channel::mpsc;
+use futures_util::stream::{self, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
 
-#[derive(Default, Debug, Clone)]
+#[derive(Default, Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct PriceInfo {
     pub price: Option<f64>,
     pub ui_price: String,
@@ -16,11 +28,22 @@ pub struct TokenPriceInfo {
     pub price_info: PriceInfo,
 }
 
+/// How a pair price was obtained.
+#[derive(Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PairPriceMethod {
+    /// Quoted directly with `vsToken`.
+    #[default]
+    VsToken,
+    /// Base USD price divided by quote USD price.
+    Derived,
+}
+
 #[derive(Default, Debug, Clone)]
 pub struct PairPriceInfo {
     pub token_a: Token,
     pub token_b: Token,
     pub price_info: PriceInfo,
+    pub method: PairPriceMethod,
 }
 
 #[derive(Default, Debug, Clone)]
@@ -41,12 +64,359 @@ pub enum TokenOrPairPriceInfo {
 
 pub type TokenOrPairAddress = String;
 
-pub async fn get_price_by_token_id(pool_id: PoolId) -> anyhow::Result<f64> {
-    // This now uses the refactored version from ray.rs which includes Fetcher logic
-    let pool_info = fetch_pool_info_by_id(pool_id).await?;
+/// Pool price via `RaydiumFetcher::shared`, so repeated calls share one response cache.
+pub async fn get_price_by_token_id(pool_id: PoolId) -> Result<f64> {
+    get_price_by_token_id_with(&RaydiumFetcher::shared(), pool_id).await
+}
+
+/// Same as `get_price_by_token_id`, reusing a fetcher (e.g. one built `with_cache`).
+pub async fn get_price_by_token_id_with(raydium: &RaydiumFetcher, pool_id: PoolId) -> Result<f64> {
+    let pool_info = raydium.fetch_pool_info_by_id(pool_id).await?;
 
     // Get price from pool that match id
     let price = pool_info.price;
 
     Ok(price)
 }
+
+/// Address `PriceFeeder` stores a wallet's perps PnL under, e.g. `SOL_PERPS_<wallet>`.
+pub fn perps_address(wallet_address: &str) -> TokenOrPairAddress {
+    format!("SOL_PERPS_{wallet_address}")
+}
+
+impl TokenOrPairPriceInfo {
+    pub fn price_info(&self) -> &PriceInfo {
+        match self {
+            TokenOrPairPriceInfo::Pair(pair) => &pair.price_info,
+            TokenOrPairPriceInfo::Token(token) => &token.price_info,
+            TokenOrPairPriceInfo::Perp(perp) => &perp.pnl_after_fees_usd,
+        }
+    }
+}
+
+/// Emitted by `PriceFeeder` when a value changes or an address fails to update.
+#[derive(Debug, Clone)]
+pub enum FeedEvent {
+    Updated {
+        address: TokenOrPairAddress,
+        info: Box<TokenOrPairPriceInfo>,
+    },
+    Failed {
+        address: TokenOrPairAddress,
+        error: Arc<JupSdkError>,
+    },
+}
+
+type FeedCallback = Arc<dyn Fn(&FeedEvent) + Send + Sync>;
+
+/// Polls tokens, pairs and perps wallets and keeps the latest value per address.
+///
+/// On native, `run` loops forever on tokio. In a Workers scheduled handler or Durable
+/// Object alarm, call `poll_once` per invocation instead (or `run` inside a Durable Object).
+pub struct PriceFeeder {
+    prices: PriceFetcher,
+    perps: PerpsFetcher,
+    tokens: Vec<Token>,
+    pairs: Vec<[Token; 2]>,
+    perps_wallets: Vec<String>,
+    interval: Duration,
+    latest: Mutex<HashMap<TokenOrPairAddress, TokenOrPairPriceInfo>>,
+    subscribers: Mutex<Vec<mpsc::UnboundedSender<FeedEvent>>>,
+    callbacks: Mutex<Vec<FeedCallback>>,
+}
+
+impl Default for PriceFeeder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PriceFeeder {
+    pub fn new() -> Self {
+        Self {
+            prices: PriceFetcher::new(),
+            perps: PerpsFetcher::new(),
+            tokens: Vec::new(),
+            pairs: Vec::new(),
+            perps_wallets: Vec::new(),
+            interval: Duration::from_secs(30),
+            latest: Mutex::new(HashMap::new()),
+            subscribers: Mutex::new(Vec::new()),
+            callbacks: Mutex::new(Vec::new()),
+        }
+    }
+
+    pub fn with_price_fetcher(mut self, prices: PriceFetcher) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    pub fn with_perps_fetcher(mut self, perps: PerpsFetcher) -> Self {
+        self.perps = perps;
+        self
+    }
+
+    pub fn with_tokens(mut self, tokens: Vec<Token>) -> Self {
+        self.tokens = tokens;
+        self
+    }
+
+    pub fn with_pairs(mut self, pairs: Vec<[Token; 2]>) -> Self {
+        self.pairs = pairs;
+        self
+    }
+
+    /// Tracks the total perps PnL of a wallet under `perps_address(wallet)`.
+    pub fn with_perps_wallet(mut self, wallet_address: &str) -> Self {
+        self.perps_wallets.push(wallet_address.to_string());
+        self
+    }
+
+    /// Delay between polls in `run` (30s by default).
+    pub fn with_interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+
+    /// Receives every event from now on; dropped receivers are pruned on the next poll.
+    pub fn subscribe(&self) -> mpsc::UnboundedReceiver<FeedEvent> {
+        let (sender, receiver) = mpsc::unbounded();
+        self.subscribers.lock().unwrap().push(sender);
+        receiver
+    }
+
+    /// Calls `callback` synchronously for every event. Callbacks may register
+    /// further callbacks; those receive events from the next poll on.
+    pub fn on_event(&self, callback: impl Fn(&FeedEvent) + Send + Sync + 'static) {
+        self.callbacks.lock().unwrap().push(Arc::new(callback));
+    }
+
+    pub fn latest(&self) -> HashMap<TokenOrPairAddress, TokenOrPairPriceInfo> {
+        self.latest.lock().unwrap().clone()
+    }
+
+    pub fn get(&self, address: &str) -> Option<TokenOrPairPriceInfo> {
+        self.latest.lock().unwrap().get(address).cloned()
+    }
+
+    /// Polls forever, sleeping `interval` (via `compat::sleep`) between polls.
+    pub async fn run(&self) {
+        loop {
+            self.poll_once().await;
+            compat::sleep(self.interval).await;
+        }
+    }
+
+    /// Fetches everything once, updates `latest` and returns the events it emitted.
+    /// Only values whose price changed produce `FeedEvent::Updated`.
+    pub async fn poll_once(&self) -> Vec<FeedEvent> {
+        let mut results: Vec<(
+            TokenOrPairAddress,
+            std::result::Result<TokenOrPairPriceInfo, Arc<JupSdkError>>,
+        )> = Vec::new();
+
+        if !self.tokens.is_empty() || !self.pairs.is_empty() {
+            let report = self
+                .prices
+                .fetch_many_price_and_format(self.tokens.clone(), self.pairs.clone())
+                .await;
+            results.extend(report.prices.into_iter().map(|(a, info)| (a, Ok(info))));
+            results.extend(report.errors.into_iter().map(|(a, e)| (a, Err(e))));
+        }
+
+        let perps_results: Vec<_> = stream::iter(&self.perps_wallets)
+            .map(|wallet| async move {
+                let address = perps_address(wallet);
+                let pnl = self.perps.fetch_positions_pnl_and_format(wallet).await;
+                let info = pnl.map(|pnl| {
+                    let token = token_registry::get_tokens_from_pair_address(&address)
+                        .into_iter()
+                        .next()
+                        .unwrap_or_default();
+                    TokenOrPairPriceInfo::Perp(PerpValueInfo {
+                        id: address.clone(),
+                        token,
+                        pnl_after_fees_usd: PriceInfo {
+                            price: Some(pnl.total_pnl_usd),
+                            ui_price: format_price(pnl.total_pnl_usd),
+                            updated_at: get_unix_timestamp(),
+                        },
+                    })
+                });
+                (address, info.map_err(Arc::new))
+            })
+            .buffer_unordered(4)
+            .collect()
+            .await;
+        results.extend(perps_results);
+
+        let mut events = Vec::new();
+        {
+            let mut latest = self.latest.lock().unwrap();
+            for (address, result) in results {
+                match result {
+                    Ok(info) => {
+                        let changed = latest.get(&address).is_none_or(|previous| {
+                            previous.price_info().price != info.price_info().price
+                        });
+                        latest.insert(address.clone(), info.clone());
+                        if changed {
+                            events.push(FeedEvent::Updated {
+                                address,
+                                info: Box::new(info),
+                            });
+                        }
+                    }
+                    Err(error) => events.push(FeedEvent::Failed { address, error }),
+                }
+            }
+        }
+
+        self.emit(&events);
+        events
+    }
+
+    fn emit(&self, events: &[FeedEvent]) {
+        if events.is_empty() {
+            return;
+        }
+        self.subscribers.lock().unwrap().retain(|subscriber| {
+            events
+                .iter()
+                .all(|event| subscriber.unbounded_send(event.clone()).is_ok())
+        });
+        // Call outside the lock so callbacks can call `on_event`, and a panicking
+        // callback doesn't poison it.
+        let callbacks = self.callbacks.lock().unwrap().clone();
+        for callback in &callbacks {
+            for event in events {
+                callback(event);
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::fetcher::Fetcher;
+    use crate::perps::POSITIONS_FIXTURE;
+    use crate::transport::{MockResponse, MockTransport};
+    use serde_json::json;
+
+    const WALLET: &str = "WaLLet1111111111111111111111111111111111111";
+
+    fn sol_price(price: &str) -> MockResponse {
+        let sol = "So11111111111111111111111111111111111111112";
+        MockResponse::json(
+            200,
+            &json!({
+                "data": { sol: { "id": sol, "type": "derivedPrice", "price": price } },
+                "timeTaken": 0.001
+            }),
+        )
+    }
+
+    #[tokio::test]
+    async fn test_poll_once_emits_changes_only() {
+        let sol = token_registry::get_by_address("So11111111111111111111111111111111111111112")
+            .unwrap()
+            .clone();
+        let price_url = format!("https://api.jup.ag/price/v2?ids={}", sol.address);
+        let positions_url = crate::perps::positions_url(WALLET);
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(&price_url, sol_price("150"))
+                .on(&price_url, sol_price("150"))
+                .on(&price_url, sol_price("151"))
+                .on(
+                    &positions_url,
+                    MockResponse::new(200).with_body(POSITIONS_FIXTURE.as_bytes().to_vec()),
+                ),
+        );
+        let fetcher = Fetcher::new().with_transport(mock);
+        let feeder = PriceFeeder::new()
+            .with_price_fetcher(PriceFetcher::with_fetcher(fetcher.clone()))
+            .with_perps_fetcher(PerpsFetcher::with_fetcher(fetcher))
+            .with_tokens(vec![sol.clone()])
+            .with_perps_wallet(WALLET);
+
+        let mut receiver = feeder.subscribe();
+        let seen = Arc::new(Mutex::new(0));
+        let counter = seen.clone();
+        feeder.on_event(move |_| *counter.lock().unwrap() += 1);
+
+        assert_eq!(feeder.poll_once().await.len(), 2);
+        assert!(feeder.poll_once().await.is_empty());
+        let events = feeder.poll_once().await;
+
+        assert_eq!(events.len(), 1);
+        assert!(
+            matches!(&events[0], FeedEvent::Updated { address, .. } if *address == sol.address)
+        );
+        assert_eq!(
+            feeder.get(&sol.address).unwrap().price_info().price,
+            Some(151.0)
+        );
+        let pnl = feeder.get(&perps_address(WALLET)).unwrap();
+        assert!((pnl.price_info().price.unwrap() - 101.078571).abs() < 1e-6);
+
+        assert_eq!(*seen.lock().unwrap(), 3);
+        let mut received = 0;
+        while let Ok(Some(_)) = receiver.try_next() {
+            received += 1;
+        }
+        assert_eq!(received, 3);
+    }
+
+    #[tokio::test]
+    async fn test_poll_once_reports_failures() {
+        let mock = Arc::new(MockTransport::new());
+        let feeder = PriceFeeder::new()
+            .with_perps_fetcher(PerpsFetcher::with_fetcher(
+                Fetcher::new().with_transport(mock),
+            ))
+            .with_perps_wallet(WALLET);
+
+        let events = feeder.poll_once().await;
+
+        assert!(
+            matches!(&events[..], [FeedEvent::Failed { address, .. }] if *address == perps_address(WALLET))
+        );
+        assert!(feeder.latest().is_empty());
+    }
+
+    #[test]
+    fn test_callbacks_run_outside_the_lock() {
+        let event = FeedEvent::Failed {
+            address: perps_address(WALLET),
+            error: Arc::new(JupSdkError::not_found(
+                crate::error::NotFoundKind::Token,
+                WALLET,
+            )),
+        };
+
+        // A callback registering another callback doesn't deadlock.
+        let feeder = Arc::new(PriceFeeder::new());
+        let registering = feeder.clone();
+        let calls = Arc::new(Mutex::new(0));
+        let counter = calls.clone();
+        feeder.on_event(move |_| {
+            let counter = counter.clone();
+            registering.on_event(move |_| *counter.lock().unwrap() += 1);
+        });
+        feeder.emit(std::slice::from_ref(&event));
+        feeder.emit(std::slice::from_ref(&event));
+        assert_eq!(*calls.lock().unwrap(), 1);
+
+        // A panicking callback doesn't break later registrations or polls.
+        let feeder = PriceFeeder::new();
+        feeder.on_event(|_| panic!("callback failed"));
+        let emitted = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            feeder.emit(std::slice::from_ref(&event))
+        }));
+        assert!(emitted.is_err());
+        feeder.on_event(|_| {});
+        let _receiver = feeder.subscribe();
+    }
+}
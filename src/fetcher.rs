@@ -1,6 +1,13 @@
+use crate::cache::ResponseCache;
 use crate::compat;
-use anyhow::{anyhow, Result};
-use serde::de::DeserializeOwned;
+use crate::error::{JupSdkError, Result};
+use crate::rate_limit::{EndpointFamily, RateLimiter};
+use crate::time::get_unix_timestamp;
+use crate::transport::{Method, ReqwestTransport, Transport, TransportRequest};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
 use std::time::Duration;
 
 // RetrySettings remains the same
@@ -9,6 +16,10 @@ pub struct RetrySettings {
     pub max_retries: usize,
     pub request_timeout: Duration,
     pub base_backoff: Duration,
+    /// Upper bound for any single wait, including server-provided `Retry-After`.
+    pub max_backoff: Duration,
+    /// Spreads each wait randomly over `[delay, delay * (1 + jitter)]`, within `max_backoff`.
+    pub jitter: f64,
 }
 
 impl Default for RetrySettings {
@@ -17,6 +28,8 @@ impl Default for RetrySettings {
             max_retries: 3,
             request_timeout: Duration::from_secs(10),
             base_backoff: Duration::from_secs(2), // Start with 2 seconds
+            max_backoff: Duration::from_secs(30),
+            jitter: 0.2,
         }
     }
 }
@@ -37,6 +50,32 @@ impl RetrySettings {
         self.base_backoff = backoff;
         self
     }
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+    pub fn with_jitter(mut self, jitter: f64) -> Self {
+        self.jitter = jitter.max(0.0);
+        self
+    }
+
+    /// Wait before retry number `retries`: `Retry-After` when the server sent one,
+    /// exponential backoff otherwise, capped at `max_backoff` and then jittered.
+    ///
+    /// Waits near the cap spread over `[max_backoff / (1 + jitter), max_backoff]`,
+    /// so clients that all hit the cap don't retry in lockstep.
+    pub fn backoff_for(&self, retries: u32, retry_after: Option<Duration>) -> Duration {
+        let cap = self.max_backoff.as_secs_f64();
+        let delay = retry_after
+            .unwrap_or_else(|| exponential_backoff(retries, self.base_backoff))
+            .as_secs_f64()
+            .min(cap);
+        let spread = 1.0 + self.jitter;
+        let upper = (delay * spread).min(cap);
+        let lower = delay.min(upper / spread);
+        Duration::try_from_secs_f64(lower + (upper - lower) * random_unit())
+            .unwrap_or(self.max_backoff)
+    }
 }
 
 // Helper function (remains the same)
@@ -44,129 +83,387 @@ fn exponential_backoff(retries: u32, base_backoff: Duration) -> Duration {
     if retries == 0 {
         base_backoff
     } else {
-        let exponential_factor = 2u32.pow(retries.saturating_sub(1));
-        base_backoff * exponential_factor
+        let exponential_factor = 2u32.saturating_pow(retries.saturating_sub(1));
+        base_backoff.saturating_mul(exponential_factor)
+    }
+}
+
+// Uniform-ish value in [0, 1) from std's randomly seeded hasher (no rand dependency).
+fn random_unit() -> f64 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(get_unix_timestamp());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+// `None` if the year is too large to compute with.
+fn days_from_civil(year: i64, month: u32, day: u32) -> Option<i64> {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year =
+        (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era.checked_mul(146097)?
+        .checked_add(day_of_era)?
+        .checked_sub(719468)
+}
+
+// Parses an IMF-fixdate such as `Sun, 06 Nov 1994 08:49:37 GMT` into unix seconds.
+fn parse_http_date(value: &str) -> Option<u64> {
+    let parts: Vec<&str> = value.split_whitespace().collect();
+    let [_, day, month, year, time, "GMT"] = parts.as_slice() else {
+        return None;
+    };
+    let day: u32 = day.parse().ok().filter(|day| (1..=31).contains(day))?;
+    let month = MONTHS.iter().position(|m| m == month)? as u32 + 1;
+    let year: i64 = year.parse().ok().filter(|year| (0..=9999).contains(year))?;
+    let hms: Vec<u64> = time
+        .split(':')
+        .map(|part| part.parse().ok())
+        .collect::<Option<_>>()?;
+    let [hours @ 0..24, minutes @ 0..60, seconds @ 0..61] = hms.as_slice() else {
+        return None;
+    };
+    let days = u64::try_from(days_from_civil(year, month, day)?).ok()?;
+    days.checked_mul(86_400)?
+        .checked_add(hours * 3600 + minutes * 60 + seconds)
+}
+
+/// Parses a `Retry-After` header given as delay-seconds or an HTTP-date.
+pub fn parse_retry_after(value: &str, now_unix: u64) -> Option<Duration> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<u64>() {
+        return Some(Duration::from_secs(seconds));
+    }
+    parse_http_date(value).map(|at| Duration::from_secs(at.saturating_sub(now_unix)))
+}
+
+/// Host the Jupiter API key is sent to (including subdomains such as `api.jup.ag`).
+pub const JUP_API_HOST: &str = "jup.ag";
+
+// Host of an absolute URL, without userinfo or port.
+fn url_host(url: &str) -> Option<&str> {
+    let (_, rest) = url.split_once("://")?;
+    let authority = rest.split(['/', '?', '#']).next()?;
+    let host = authority.rsplit('@').next()?;
+    match host.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next(),
+        None => host.split(':').next(),
+    }
+}
+
+// Whether `host` is `domain` or one of its subdomains.
+fn host_matches(host: &str, domain: &str) -> bool {
+    host.len() >= domain.len()
+        && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+        && (host.len() == domain.len() || host.as_bytes()[host.len() - domain.len() - 1] == b'.')
+}
+
+// A header the fetcher adds to requests, optionally only for one domain.
+#[derive(Debug, Clone)]
+struct DefaultHeader {
+    domain: Option<String>,
+    key: String,
+    value: String,
+}
+
+// Percent-encodes a query component (RFC 3986 unreserved characters pass through).
+fn encode_query_component(value: &str) -> String {
+    value
+        .bytes()
+        .map(|b| match b {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                (b as char).to_string()
+            }
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// Builder for a single request sent through a `Fetcher`.
+///
+/// Default headers from the `Fetcher` are applied first; headers set here override them.
+pub struct RequestBuilder<'a> {
+    fetcher: &'a Fetcher,
+    method: Method,
+    url: String,
+    query: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+    body: Option<std::result::Result<Vec<u8>, serde_json::Error>>,
+}
+
+impl<'a> RequestBuilder<'a> {
+    pub fn header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Appends a percent-encoded query parameter to the URL.
+    pub fn query(mut self, key: &str, value: impl ToString) -> Self {
+        self.query.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    /// Sets a JSON body and the matching `content-type` header.
+    pub fn json<B: Serialize>(mut self, body: &B) -> Self {
+        self.body = Some(serde_json::to_vec(body));
+        self.header("content-type", "application/json")
+    }
+
+    fn build(self) -> Result<TransportRequest> {
+        let mut url = self.url;
+        for (i, (key, value)) in self.query.iter().enumerate() {
+            let separator = if i == 0 && !url.contains('?') {
+                '?'
+            } else {
+                '&'
+            };
+            url.push(separator);
+            url.push_str(&encode_query_component(key));
+            url.push('=');
+            url.push_str(&encode_query_component(value));
+        }
+
+        let host = url_host(&url);
+        let mut headers: Vec<(String, String)> = self
+            .fetcher
+            .default_headers
+            .iter()
+            .filter(|header| match (&header.domain, host) {
+                (None, _) => true,
+                (Some(domain), Some(host)) => host_matches(host, domain),
+                (Some(_), None) => false,
+            })
+            .filter(|header| {
+                !self
+                    .headers
+                    .iter()
+                    .any(|(override_key, _)| override_key.eq_ignore_ascii_case(&header.key))
+            })
+            .map(|header| (header.key.clone(), header.value.clone()))
+            .collect();
+        headers.extend(self.headers);
+
+        let body = match self.body {
+            Some(Ok(body)) => Some(body),
+            Some(Err(source)) => return Err(JupSdkError::Serialize { url, source }),
+            None => None,
+        };
+
+        Ok(TransportRequest {
+            method: self.method,
+            url,
+            headers,
+            body,
+        })
+    }
+
+    /// Sends the request with the fetcher's retry, backoff and timeout semantics.
+    pub async fn send<T: DeserializeOwned + Send + 'static>(self) -> Result<T> {
+        let fetcher = self.fetcher;
+        let request = self.build()?;
+        let url = request.url.clone();
+        let body = match (&fetcher.cache, request.method) {
+            (Some(cache), Method::Get) => cache.get_or_fetch(fetcher, request).await?,
+            _ => fetcher.send_with_retry(request).await?,
+        };
+        serde_json::from_slice::<T>(&body)
+            .map_err(|source| JupSdkError::Deserialize { url, source })
     }
 }
 
-// --- Unified Fetcher Implementation using reqwest ---
+// --- Unified Fetcher Implementation over a pluggable Transport ---
+#[derive(Clone)]
 pub struct Fetcher {
-    client: reqwest::Client,
+    transport: Arc<dyn Transport>,
     settings: RetrySettings,
+    default_headers: Vec<DefaultHeader>,
+    rate_limiter: Option<Arc<RateLimiter>>,
+    endpoint_family: Option<EndpointFamily>,
+    cache: Option<Arc<ResponseCache>>,
 }
 
 impl Fetcher {
     pub fn new() -> Self {
-        Self {
-            client: reqwest::Client::new(),
-            settings: RetrySettings::default(),
-        }
+        Self::with_settings(RetrySettings::default())
     }
 
     pub fn with_settings(settings: RetrySettings) -> Self {
         Self {
-            client: reqwest::Client::new(),
+            transport: Arc::new(ReqwestTransport::new()),
             settings,
+            default_headers: Vec::new(),
+            rate_limiter: None,
+            endpoint_family: None,
+            cache: None,
         }
     }
 
+    /// Replaces the HTTP transport, e.g. with a `MockTransport` in tests.
+    pub fn with_transport(mut self, transport: Arc<dyn Transport>) -> Self {
+        self.transport = transport;
+        self
+    }
+
+    /// Waits on a (shared) client-side rate limiter before every attempt.
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<RateLimiter>) -> Self {
+        self.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Tags requests so the rate limiter can apply a per-family quota.
+    pub fn with_endpoint_family(mut self, family: EndpointFamily) -> Self {
+        self.endpoint_family = Some(family);
+        self
+    }
+
+    /// Serves GET requests through a (shared) response cache.
+    pub fn with_cache(mut self, cache: Arc<ResponseCache>) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Adds a header sent with every request made through this fetcher.
+    pub fn with_default_header(self, key: &str, value: &str) -> Self {
+        self.push_default_header(None, key, value)
+    }
+
+    /// Adds a header sent only with requests to `domain` and its subdomains.
+    pub fn with_domain_header(self, domain: &str, key: &str, value: &str) -> Self {
+        self.push_default_header(Some(domain), key, value)
+    }
+
+    /// Sends `x-api-key` with requests to `*.jup.ag` (required by the paid api.jup.ag tiers).
+    ///
+    /// Other hosts, such as Raydium, never see the key.
+    pub fn with_api_key(self, api_key: &str) -> Self {
+        self.with_domain_header(JUP_API_HOST, "x-api-key", api_key)
+    }
+
+    fn push_default_header(mut self, domain: Option<&str>, key: &str, value: &str) -> Self {
+        let domain = domain.map(str::to_string);
+        self.default_headers.retain(|existing| {
+            !(existing.domain == domain && existing.key.eq_ignore_ascii_case(key))
+        });
+        self.default_headers.push(DefaultHeader {
+            domain,
+            key: key.to_string(),
+            value: value.to_string(),
+        });
+        self
+    }
+
+    pub fn request(&self, method: Method, url: &str) -> RequestBuilder<'_> {
+        RequestBuilder {
+            fetcher: self,
+            method,
+            url: url.to_string(),
+            query: Vec::new(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+
+    pub fn get(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::Get, url)
+    }
+
+    pub fn post(&self, url: &str) -> RequestBuilder<'_> {
+        self.request(Method::Post, url)
+    }
+
     pub async fn fetch_with_retry<T: DeserializeOwned + Send + 'static>(
         &self,
         url: &str, // Input URL is still a slice for the public API
     ) -> Result<T> {
-        let url_owned = url.to_string(); // Create owned String immediately
+        self.get(url).send().await
+    }
+
+    /// POSTs `body` as JSON with the same retry/timeout semantics as `fetch_with_retry`.
+    pub async fn post_with_retry<B: Serialize, T: DeserializeOwned + Send + 'static>(
+        &self,
+        url: &str,
+        body: &B,
+    ) -> Result<T> {
+        self.post(url).json(body).send().await
+    }
+
+    /// Sends `request` with retries and returns the body of the successful response.
+    pub(crate) async fn send_with_retry(&self, request: TransportRequest) -> Result<Vec<u8>> {
+        let url_owned = request.url.clone();
         let mut retries = 0;
 
         loop {
-            // Clone the client and URL for this attempt to move into the async block.
-            let client_clone = self.client.clone(); // Clone the client handle
+            if let Some(rate_limiter) = &self.rate_limiter {
+                rate_limiter.acquire(&url_owned, self.endpoint_family).await;
+            }
+
+            // Clone the transport handle and request to move into the async block.
+            let transport = self.transport.clone();
+            let request = request.clone();
             let url_for_attempt = url_owned.clone();
 
             // Define the request sending future within the loop using async move
             let send_future = async move {
-                // Use async move
-                client_clone
-                    .get(&url_for_attempt) // Use the client clone
-                    .send()
-                    .await // Send the request
-                    .map_err(anyhow::Error::from) // Map reqwest::Error to anyhow::Error
+                transport
+                    .send(request)
+                    .await
+                    .map_err(|source| JupSdkError::Transport {
+                        url: url_for_attempt,
+                        source,
+                    })
             };
 
             // Wrap the send future with the timeout
             match compat::timeout(self.settings.request_timeout, send_future).await {
                 Ok(response) => {
                     // Single Ok: timeout completed, future succeeded
-                    let status = response.status();
-                    if status.is_success() {
-                        match response.json::<T>().await {
-                            Ok(data) => return Ok(data), // Success!
-                            Err(e) => {
-                                // Deserialization error
-                                return Err(anyhow!(
-                                    "Failed to deserialize response from {}: {}",
-                                    url_owned,
-                                    e
-                                )
-                                .context(format!("Status: {}", status)));
-                            }
-                        }
+                    let status = response.status;
+                    if response.is_success() {
+                        return Ok(response.body);
                     } else {
-                        // Non-success status code
-                        let error_body_result = response.text().await;
-
-                        if status.is_server_error() && retries < self.settings.max_retries {
+                        // Non-success status code: 5xx and 429 (rate limited) are retryable
+                        let is_retryable_status = response.is_server_error() || status == 429;
+                        if is_retryable_status && retries < self.settings.max_retries {
                             retries += 1;
+                            let retry_after = response
+                                .header("retry-after")
+                                .and_then(|value| parse_retry_after(value, get_unix_timestamp()));
+                            let delay = self.settings.backoff_for(retries as u32, retry_after);
                             crate::platform_log!(
                                 warn,
-                                "Request to {} failed (attempt {}/{}): Status {}. Retrying...",
+                                "Request to {} failed (attempt {}/{}): Status {}. Retrying in {:?}...",
                                 url_owned,
                                 retries,
                                 self.settings.max_retries + 1,
-                                status
+                                status,
+                                delay
                             );
-                            let delay =
-                                exponential_backoff(retries as u32, self.settings.base_backoff);
                             compat::sleep(delay).await;
                             continue; // Retry loop
                         } else {
-                            // Client error or max retries hit for 5xx
-                            let error_body = error_body_result
-                                .unwrap_or_else(|e| format!("Failed to read error body: {}", e));
-                            return Err(anyhow!(
-                                "Request to {} failed: Status {}, Body: {}",
-                                url_owned,
+                            // Client error or max retries hit for 5xx/429
+                            return Err(JupSdkError::HttpStatus {
+                                url: url_owned,
                                 status,
-                                error_body
-                            ));
+                                body: response.text(),
+                            });
                         }
                     }
                 }
-                // Timeout completed, but the inner future failed, OR timeout elapsed
+                // Timeout elapsed, or the transport failed before a response arrived
                 Err(e) => {
-                    // e: anyhow::Error (could be timeout or reqwest error)
-                    let is_timeout_error = e.to_string().contains("timed out");
-
-                    // Conditionally check for retryable errors based on target
-                    let is_underlying_retryable = if !is_timeout_error {
-                        #[cfg(not(target_arch = "wasm32"))]
-                        {
-                            // Native target: Check specific error kinds if possible
-                            e.downcast_ref::<reqwest::Error>()
-                                .is_some_and(|re| re.is_connect() || re.is_request())
-                        }
-                        #[cfg(target_arch = "wasm32")]
-                        {
-                            // WASM target: Assume reqwest::Error here is likely a fetch/network issue and retryable.
-                            // We can't reliably use is_connect() or is_request().
-                            e.downcast_ref::<reqwest::Error>().is_some()
-                        }
-                    } else {
-                        false // It's a timeout error, handled by is_timeout_error flag
-                    };
+                    if !e.is_retryable() {
+                        return Err(e);
+                    }
 
-                    if (is_timeout_error || is_underlying_retryable)
-                        && retries < self.settings.max_retries
-                    {
+                    if retries < self.settings.max_retries {
                         retries += 1;
                         crate::platform_log!(
                             warn,
@@ -176,24 +473,15 @@ impl Fetcher {
                             self.settings.max_retries + 1,
                             e
                         );
-                        let delay = exponential_backoff(retries as u32, self.settings.base_backoff);
+                        let delay = self.settings.backoff_for(retries as u32, None);
                         compat::sleep(delay).await;
                         continue; // Retry loop
                     } else {
-                        // Max retries reached or non-retryable error
-                        let failure_context = if (is_timeout_error || is_underlying_retryable)
-                            && retries >= self.settings.max_retries
-                        {
-                            format!("after {} attempts", self.settings.max_retries + 1)
-                        } else if is_timeout_error {
-                            "due to timeout".to_string()
-                        } else {
-                            "due to non-retryable error".to_string()
-                        };
-                        return Err(e.context(format!(
-                            "Request to {} failed {}",
-                            url_owned, failure_context
-                        )));
+                        return Err(JupSdkError::RetriesExhausted {
+                            url: url_owned,
+                            attempts: self.settings.max_retries + 1,
+                            source: Box::new(e),
+                        });
                     }
                 }
             }
@@ -214,7 +502,9 @@ impl Default for Fetcher {
 #[cfg(not(target_arch = "wasm32"))] // Keep tests gated for native for now
 mod tests {
     use super::*;
+    use crate::transport::{MockResponse, MockTransport, TransportErrorKind};
     use serde::Deserialize;
+    use serde_json::json;
 
     fn setup() {
         #[cfg(all(feature = "log-native", feature = "native"))]
@@ -253,12 +543,22 @@ mod tests {
         assert!(result.is_err());
         let err = result.unwrap_err();
         assert!(err.to_string().contains("after 3 attempts"));
-        let root_cause = err.root_cause();
+        let JupSdkError::RetriesExhausted {
+            attempts, source, ..
+        } = err
+        else {
+            panic!("Expected RetriesExhausted, got {:?}", err);
+        };
+        assert_eq!(attempts, 3);
         // Native check for underlying error
-        let is_timeout_or_connect = root_cause
-            .downcast_ref::<reqwest::Error>()
-            .is_some_and(|e| e.is_timeout() || e.is_connect())
-            || root_cause.to_string().contains("timed out");
+        let is_timeout_or_connect = match *source {
+            JupSdkError::Timeout { .. } => true,
+            JupSdkError::Transport { ref source, .. } => matches!(
+                source.kind(),
+                TransportErrorKind::Timeout | TransportErrorKind::Connect
+            ),
+            _ => false,
+        };
         assert!(
             is_timeout_or_connect,
             "Error should be due to timeout or connection issue"
@@ -275,6 +575,7 @@ mod tests {
         let err = result.unwrap_err();
         assert!(err.to_string().contains("Status 404"));
         assert!(!err.to_string().contains("attempts"));
+        assert!(matches!(err, JupSdkError::HttpStatus { status: 404, .. }));
         Ok(())
     }
 
@@ -293,5 +594,272 @@ mod tests {
         assert!(err.to_string().contains("Status 503"));
         assert!(err.to_string().contains("failed: Status 503"));
         assert!(!err.to_string().contains("attempts"));
+        assert!(matches!(err, JupSdkError::HttpStatus { status: 503, .. }));
+    }
+
+    fn mock_fetcher(mock: &Arc<MockTransport>) -> Fetcher {
+        let settings = RetrySettings::default()
+            .with_request_timeout(Duration::from_millis(100))
+            .with_base_backoff(Duration::from_millis(1))
+            .with_max_retries(2);
+        Fetcher::with_settings(settings).with_transport(mock.clone())
+    }
+
+    const MOCK_URL: &str = "https://mock.local/todos/1";
+
+    fn todo_json() -> serde_json::Value {
+        json!({ "userId": 1, "id": 1, "title": "mock", "completed": false })
+    }
+
+    #[tokio::test]
+    async fn test_mock_5xx_then_success() -> Result<()> {
+        setup();
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(MOCK_URL, MockResponse::new(503))
+                .on(MOCK_URL, MockResponse::json(200, &todo_json())),
+        );
+        let todo: TestTodo = mock_fetcher(&mock).fetch_with_retry(MOCK_URL).await?;
+        assert_eq!(todo.title, "mock");
+        assert_eq!(mock.request_count(MOCK_URL), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_timeout_retry() {
+        setup();
+        let mock = Arc::new(MockTransport::new().on(
+            MOCK_URL,
+            MockResponse::json(200, &todo_json()).with_delay(Duration::from_millis(500)),
+        ));
+        let result: Result<TestTodo> = mock_fetcher(&mock).fetch_with_retry(MOCK_URL).await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("after 3 attempts"));
+        assert!(matches!(
+            err,
+            JupSdkError::RetriesExhausted { attempts: 3, ref source, .. }
+                if matches!(**source, JupSdkError::Timeout { .. })
+        ));
+        assert_eq!(mock.request_count(MOCK_URL), 3);
+    }
+
+    #[tokio::test]
+    async fn test_mock_connect_error_retry() -> Result<()> {
+        setup();
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(MOCK_URL, MockResponse::error(TransportErrorKind::Connect))
+                .on(MOCK_URL, MockResponse::json(200, &todo_json())),
+        );
+        let todo: TestTodo = mock_fetcher(&mock).fetch_with_retry(MOCK_URL).await?;
+        assert_eq!(todo.id, 1);
+        assert_eq!(mock.request_count(MOCK_URL), 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_deserialize_error_no_retry() {
+        setup();
+        let mock = Arc::new(
+            MockTransport::new().on(MOCK_URL, MockResponse::json(200, &json!({ "id": "x" }))),
+        );
+        let result: Result<TestTodo> = mock_fetcher(&mock).fetch_with_retry(MOCK_URL).await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Failed to deserialize response"));
+        assert!(matches!(err, JupSdkError::Deserialize { .. }));
+        assert_eq!(mock.request_count(MOCK_URL), 1);
+    }
+
+    #[tokio::test]
+    async fn test_mock_404_no_retry() {
+        setup();
+        let mock = Arc::new(MockTransport::new().on(MOCK_URL, MockResponse::text(404, "nope")));
+        let result: Result<TestTodo> = mock_fetcher(&mock).fetch_with_retry(MOCK_URL).await;
+        let err = result.unwrap_err();
+        assert!(err.to_string().contains("Status 404, Body: nope"));
+        assert!(matches!(err, JupSdkError::HttpStatus { status: 404, .. }));
+        assert_eq!(mock.request_count(MOCK_URL), 1);
+    }
+
+    #[tokio::test]
+    async fn test_request_builder_headers_query_and_body() -> Result<()> {
+        setup();
+        let url = "https://mock.local/todos?userId=1&q=a%2Cb%20c";
+        let mock = Arc::new(MockTransport::new().on(url, MockResponse::json(200, &todo_json())));
+        let fetcher = mock_fetcher(&mock)
+            .with_api_key("default-key")
+            .with_default_header("x-client", "jup-sdk");
+
+        let todo: TestTodo = fetcher
+            .post("https://mock.local/todos?userId=1")
+            .query("q", "a,b c")
+            .header("X-API-KEY", "override-key")
+            .json(&json!({ "title": "mock" }))
+            .send()
+            .await?;
+        assert_eq!(todo.id, 1);
+
+        let request = mock.requests().pop().unwrap();
+        assert_eq!(request.method, Method::Post);
+        assert_eq!(request.url, url);
+        let header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .filter(|(key, _)| key.eq_ignore_ascii_case(name))
+                .map(|(_, value)| value.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(header("x-api-key"), vec!["override-key"]);
+        assert_eq!(header("x-client"), vec!["jup-sdk"]);
+        assert_eq!(header("content-type"), vec!["application/json"]);
+        assert_eq!(request.body.as_deref(), Some(&br#"{"title":"mock"}"#[..]));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_api_key_only_sent_to_jup_hosts() -> Result<()> {
+        setup();
+        let urls = [
+            "https://api.jup.ag/price/v2",
+            "https://user@lite-api.jup.ag:443/price/v2",
+            "https://jup.ag",
+            "https://notjup.ag/price",
+            "https://api-v3.raydium.io/pools?jup.ag",
+            "https://[::1]:8080/jup.ag",
+        ];
+        let mock = urls.iter().fold(MockTransport::new(), |mock, url| {
+            mock.on(url, MockResponse::json(200, &json!({})))
+        });
+        let mock = Arc::new(mock);
+        let fetcher = mock_fetcher(&mock)
+            .with_api_key("old-key")
+            .with_api_key("secret")
+            .with_default_header("x-client", "jup-sdk");
+
+        for url in urls {
+            let _: serde_json::Value = fetcher.fetch_with_retry(url).await?;
+        }
+
+        let api_keys: Vec<Option<String>> = mock
+            .requests()
+            .iter()
+            .map(|request| {
+                assert!(request
+                    .headers
+                    .contains(&("x-client".to_string(), "jup-sdk".to_string())));
+                request
+                    .headers
+                    .iter()
+                    .find(|(key, _)| key == "x-api-key")
+                    .map(|(_, value)| value.clone())
+            })
+            .collect();
+        let secret = Some("secret".to_string());
+        assert_eq!(
+            api_keys,
+            vec![secret.clone(), secret.clone(), secret, None, None, None]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_retry_after() {
+        assert_eq!(parse_retry_after(" 7 ", 0), Some(Duration::from_secs(7)));
+        // 784111777 == Sun, 06 Nov 1994 08:49:37 GMT
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", 784_111_770),
+            Some(Duration::from_secs(7))
+        );
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:37 GMT", 784_111_800),
+            Some(Duration::ZERO)
+        );
+        assert_eq!(parse_retry_after("soon", 0), None);
+        for malformed in [
+            "Sun, 06 Nov 9999999999999999 99999999999:00:00 GMT",
+            "Sun, 06 Nov -9223372036854775808 08:49:37 GMT",
+            "Sun, 00 Nov 1994 08:49:37 GMT",
+            "Sun, 32 Nov 1994 08:49:37 GMT",
+            "Sun, 06 Nov 1994 24:00:00 GMT",
+            "Sun, 06 Nov 1994 08:60:00 GMT",
+            "Sun, 06 Nov 1994 08:49:61 GMT",
+            "Sun, 06 Nov 1994 18446744073709551615:00:00 GMT",
+        ] {
+            assert_eq!(parse_retry_after(malformed, 0), None, "{}", malformed);
+        }
+        // Leap second and the last representable year still parse.
+        assert_eq!(
+            parse_retry_after("Sun, 06 Nov 1994 08:49:60 GMT", 784_111_770),
+            Some(Duration::from_secs(30))
+        );
+        assert!(parse_retry_after("Fri, 31 Dec 9999 23:59:59 GMT", 0).is_some());
+    }
+
+    #[test]
+    fn test_backoff_jitter_and_cap() {
+        let settings = RetrySettings::default()
+            .with_base_backoff(Duration::from_secs(1))
+            .with_max_backoff(Duration::from_secs(5))
+            .with_jitter(0.5);
+        for _ in 0..50 {
+            let delay = settings.backoff_for(2, None);
+            assert!(delay >= Duration::from_secs(2) && delay <= Duration::from_secs(3));
+            let delay = settings.backoff_for(1, Some(Duration::from_secs(1)));
+            assert!(delay >= Duration::from_secs(1) && delay <= Duration::from_millis(1500));
+        }
+
+        // Delays at the cap are jittered below it instead of all equal to it.
+        let capped: Vec<Duration> = (0..50)
+            .map(|_| settings.backoff_for(40, None))
+            .chain((0..50).map(|_| settings.backoff_for(1, Some(Duration::from_secs(3600)))))
+            .collect();
+        let floor = Duration::from_secs(5).div_f64(1.5);
+        assert!(capped
+            .iter()
+            .all(|d| *d >= floor && *d <= Duration::from_secs(5)));
+        assert!(capped.iter().any(|d| *d != capped[0]));
+    }
+
+    #[test]
+    fn test_backoff_huge_retry_after() {
+        let settings = RetrySettings::default();
+        let retry_after = parse_retry_after("18446744073709551615", 0);
+        assert_eq!(retry_after, Some(Duration::from_secs(u64::MAX)));
+        assert!(settings.backoff_for(1, retry_after) <= settings.max_backoff);
+
+        let settings = settings
+            .with_max_backoff(Duration::MAX)
+            .with_jitter(f64::MAX);
+        // Doesn't panic even when the jittered wait can't be represented.
+        settings.backoff_for(1, retry_after);
+    }
+
+    #[tokio::test]
+    async fn test_mock_429_retry_after() -> Result<()> {
+        setup();
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    MOCK_URL,
+                    MockResponse::new(429).with_header("Retry-After", "0"),
+                )
+                .on(MOCK_URL, MockResponse::json(200, &todo_json())),
+        );
+        let fetcher = Fetcher::with_settings(
+            RetrySettings::default()
+                // A huge base backoff proves the Retry-After value was preferred.
+                .with_base_backoff(Duration::from_secs(60))
+                .with_max_retries(1),
+        )
+        .with_transport(mock.clone());
+
+        let todo: TestTodo = compat::timeout(Duration::from_secs(2), async move {
+            fetcher.fetch_with_retry(MOCK_URL).await
+        })
+        .await?;
+        assert_eq!(todo.id, 1);
+        assert_eq!(mock.request_count(MOCK_URL), 2);
+        Ok(())
     }
 }
@@ -8,6 +8,7 @@ pub fn get_label_and_ui_price(price_info: &TokenOrPairPriceInfo) -> (String, Str
             token_a,
             token_b,
             price_info,
+            ..
         }) => {
             let label = format!("{}/{}", token_a.symbol, token_b.symbol);
             let ui_price = price_info
@@ -48,7 +49,7 @@ pub fn get_label_and_ui_price(price_info: &TokenOrPairPriceInfo) -> (String, Str
 }
 
 /// Formats a price result into a user-friendly string.
-pub fn format_price_result(result: anyhow::Result<f64>) -> Option<String> {
+pub fn format_price_result<E>(result: Result<f64, E>) -> Option<String> {
     result
         .ok()
         .map(format_price)
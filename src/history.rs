@@ -0,0 +1,348 @@
+use crate::feeder::{FeedEvent, TokenOrPairAddress, TokenOrPairPriceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use strum_macros::Display;
+
+/// Candle width.
+#[derive(Display, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resolution {
+    #[serde(rename = "1m")]
+    #[strum(serialize = "1m")]
+    OneMinute,
+    #[serde(rename = "5m")]
+    #[strum(serialize = "5m")]
+    FiveMinutes,
+    #[serde(rename = "1h")]
+    #[strum(serialize = "1h")]
+    OneHour,
+    #[serde(rename = "1d")]
+    #[strum(serialize = "1d")]
+    OneDay,
+}
+
+impl Resolution {
+    pub const ALL: [Resolution; 4] = [
+        Resolution::OneMinute,
+        Resolution::FiveMinutes,
+        Resolution::OneHour,
+        Resolution::OneDay,
+    ];
+
+    pub fn secs(&self) -> u64 {
+        match self {
+            Resolution::OneMinute => 60,
+            Resolution::FiveMinutes => 5 * 60,
+            Resolution::OneHour => 60 * 60,
+            Resolution::OneDay => 24 * 60 * 60,
+        }
+    }
+
+    /// Start of the (UTC-aligned) candle containing `timestamp`.
+    pub fn bucket_start(&self, timestamp: u64) -> u64 {
+        timestamp - timestamp % self.secs()
+    }
+}
+
+/// One recorded price (`timestamp` in unix seconds).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub timestamp: u64,
+    pub price: f64,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Candle {
+    /// Unix seconds at which the candle opens.
+    pub start: u64,
+    pub open: f64,
+    pub high: f64,
+    pub low: f64,
+    pub close: f64,
+    /// Number of samples aggregated into the candle.
+    pub samples: u32,
+}
+
+impl Candle {
+    fn new(start: u64, price: f64) -> Self {
+        Self {
+            start,
+            open: price,
+            high: price,
+            low: price,
+            close: price,
+            samples: 1,
+        }
+    }
+
+    fn update(&mut self, price: f64) {
+        self.high = self.high.max(price);
+        self.low = self.low.min(price);
+        self.close = price;
+        self.samples += 1;
+    }
+}
+
+#[derive(Debug, Default)]
+struct Series {
+    samples: VecDeque<Sample>,
+    candles: HashMap<Resolution, VecDeque<Candle>>,
+}
+
+/// Per-address price history: a ring buffer of raw samples plus OHLC candles
+/// at every `Resolution`, each capped in length.
+#[derive(Debug)]
+pub struct PriceHistory {
+    max_samples: usize,
+    max_candles: usize,
+    series: HashMap<TokenOrPairAddress, Series>,
+}
+
+impl Default for PriceHistory {
+    /// One day of samples at a 30s poll interval and 500 candles per resolution.
+    fn default() -> Self {
+        Self::new(2880, 500)
+    }
+}
+
+impl PriceHistory {
+    pub fn new(max_samples: usize, max_candles: usize) -> Self {
+        Self {
+            max_samples,
+            max_candles,
+            series: HashMap::new(),
+        }
+    }
+
+    /// Records a sample. Samples older than the latest one for `address` are ignored.
+    pub fn record(&mut self, address: &str, price: f64, timestamp: u64) {
+        let series = self.series.entry(address.to_string()).or_default();
+        if series
+            .samples
+            .back()
+            .is_some_and(|last| timestamp < last.timestamp)
+        {
+            return;
+        }
+
+        series.samples.push_back(Sample { timestamp, price });
+        while series.samples.len() > self.max_samples {
+            series.samples.pop_front();
+        }
+
+        for resolution in Resolution::ALL {
+            let start = resolution.bucket_start(timestamp);
+            let candles = series.candles.entry(resolution).or_default();
+            match candles.back_mut() {
+                Some(candle) if candle.start == start => candle.update(price),
+                _ => candles.push_back(Candle::new(start, price)),
+            }
+            while candles.len() > self.max_candles {
+                candles.pop_front();
+            }
+        }
+    }
+
+    /// Records a feeder value at its `updated_at`; values without a price are ignored.
+    pub fn record_info(&mut self, address: &str, info: &TokenOrPairPriceInfo) {
+        let price_info = info.price_info();
+        if let Some(price) = price_info.price {
+            self.record(address, price, price_info.updated_at);
+        }
+    }
+
+    pub fn record_event(&mut self, event: &FeedEvent) {
+        if let FeedEvent::Updated { address, info } = event {
+            self.record_info(address, info);
+        }
+    }
+
+    pub fn addresses(&self) -> impl Iterator<Item = &TokenOrPairAddress> {
+        self.series.keys()
+    }
+
+    /// Retained samples, oldest first.
+    pub fn samples(&self, address: &str) -> Vec<Sample> {
+        self.series
+            .get(address)
+            .map(|series| series.samples.iter().copied().collect())
+            .unwrap_or_default()
+    }
+
+    pub fn latest(&self, address: &str) -> Option<Sample> {
+        self.series.get(address)?.samples.back().copied()
+    }
+
+    /// The last `n` candles (including the one still open), oldest first.
+    pub fn candles(&self, address: &str, resolution: Resolution, n: usize) -> Vec<Candle> {
+        let Some(candles) = self
+            .series
+            .get(address)
+            .and_then(|series| series.candles.get(&resolution))
+        else {
+            return Vec::new();
+        };
+        candles
+            .iter()
+            .skip(candles.len().saturating_sub(n))
+            .copied()
+            .collect()
+    }
+
+    /// Lowest and highest price seen in `[from, to]`.
+    ///
+    /// Uses raw samples when they reach back to `from`, otherwise the finest
+    /// candles that do (which may include a little before `from`).
+    pub fn min_max(&self, address: &str, from: u64, to: u64) -> Option<(f64, f64)> {
+        let series = self.series.get(address)?;
+        let fold = |acc: Option<(f64, f64)>, (low, high): (f64, f64)| {
+            Some(acc.map_or((low, high), |(min, max)| (min.min(low), max.max(high))))
+        };
+
+        if series.samples.front().is_some_and(|s| s.timestamp <= from) {
+            return series
+                .samples
+                .iter()
+                .filter(|s| s.timestamp >= from && s.timestamp <= to)
+                .map(|s| (s.price, s.price))
+                .fold(None, fold);
+        }
+
+        let (resolution, candles) = self.candles_covering(series, from)?;
+        candles
+            .iter()
+            .filter(|c| c.start + resolution.secs() > from && c.start <= to)
+            .map(|c| (c.low, c.high))
+            .fold(None, fold)
+    }
+
+    /// Percent change from the price at `since` to the latest price.
+    pub fn percent_change_since(&self, address: &str, since: u64) -> Option<f64> {
+        let series = self.series.get(address)?;
+        let latest = series.samples.back()?.price;
+
+        let reference = if series.samples.front().is_some_and(|s| s.timestamp <= since) {
+            // Last sample at or before `since`.
+            series
+                .samples
+                .iter()
+                .rev()
+                .find(|s| s.timestamp <= since)
+                .map(|s| s.price)?
+        } else {
+            let (resolution, candles) = self.candles_covering(series, since)?;
+            candles
+                .iter()
+                .find(|c| c.start + resolution.secs() > since)
+                .map(|c| c.open)?
+        };
+
+        if reference == 0.0 {
+            return None;
+        }
+        Some((latest - reference) / reference * 100.0)
+    }
+
+    // The finest resolution whose retained candles reach back to `from`.
+    fn candles_covering<'a>(
+        &self,
+        series: &'a Series,
+        from: u64,
+    ) -> Option<(Resolution, &'a VecDeque<Candle>)> {
+        Resolution::ALL.into_iter().find_map(|resolution| {
+            let candles = series.candles.get(&resolution)?;
+            candles
+                .front()
+                .filter(|c| c.start <= from)
+                .map(|_| (resolution, candles))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    // 2025-01-01T00:00:00Z
+    const T0: u64 = 1_735_689_600;
+
+    #[test]
+    fn test_candles_aggregate_samples() {
+        let mut history = PriceHistory::default();
+        for (offset, price) in [
+            (0, 100.0),
+            (20, 105.0),
+            (40, 95.0),
+            (59, 101.0),
+            (60, 102.0),
+        ] {
+            history.record(SOL, price, T0 + offset);
+        }
+
+        let minutes = history.candles(SOL, Resolution::OneMinute, 10);
+        assert_eq!(minutes.len(), 2);
+        assert_eq!(
+            minutes[0],
+            Candle {
+                start: T0,
+                open: 100.0,
+                high: 105.0,
+                low: 95.0,
+                close: 101.0,
+                samples: 4,
+            }
+        );
+        assert_eq!(minutes[1].open, 102.0);
+
+        let hours = history.candles(SOL, Resolution::OneHour, 10);
+        assert_eq!(hours.len(), 1);
+        assert_eq!(
+            (hours[0].high, hours[0].low, hours[0].close),
+            (105.0, 95.0, 102.0)
+        );
+        assert_eq!(
+            history.candles(SOL, Resolution::OneMinute, 1)[0].start,
+            T0 + 60
+        );
+    }
+
+    #[test]
+    fn test_ring_buffer_and_out_of_order_samples() {
+        let mut history = PriceHistory::new(3, 2);
+        for i in 0..5 {
+            history.record(SOL, 100.0 + i as f64, T0 + i * 60);
+        }
+        history.record(SOL, 1.0, T0);
+
+        let samples = history.samples(SOL);
+        assert_eq!(samples.len(), 3);
+        assert_eq!(samples[0].price, 102.0);
+        assert_eq!(history.latest(SOL).unwrap().price, 104.0);
+        assert_eq!(history.candles(SOL, Resolution::OneMinute, 10).len(), 2);
+    }
+
+    #[test]
+    fn test_min_max_and_percent_change() {
+        let mut history = PriceHistory::new(10, 100);
+        // One sample per minute for an hour: 100, 101, ..., 159.
+        for i in 0..60 {
+            history.record(SOL, 100.0 + i as f64, T0 + i * 60);
+        }
+
+        // Within the retained samples (the last 10 minutes).
+        assert_eq!(
+            history.min_max(SOL, T0 + 55 * 60, T0 + 57 * 60),
+            Some((155.0, 157.0))
+        );
+        assert_eq!(
+            history.percent_change_since(SOL, T0 + 58 * 60),
+            Some((159.0 - 158.0) / 158.0 * 100.0)
+        );
+
+        // Older ranges fall back to 1m candles.
+        assert_eq!(history.min_max(SOL, T0, T0 + 30 * 60), Some((100.0, 130.0)));
+        assert_eq!(history.percent_change_since(SOL, T0), Some(59.0));
+        assert_eq!(history.percent_change_since("unknown", T0), None);
+    }
+}
@@ -0,0 +1,526 @@
+use crate::feeder::PriceInfo;
+use crate::history::{Candle, Sample};
+use std::collections::VecDeque;
+
+/// A streaming indicator: feed one input per tick, get the current value once warmed up.
+pub trait Indicator {
+    type Input;
+    type Output;
+
+    fn update(&mut self, input: Self::Input) -> Option<Self::Output>;
+}
+
+/// Runs `indicator` over `inputs`, keeping one output (or `None` while warming up) per input.
+pub fn series<I: Indicator>(
+    mut indicator: I,
+    inputs: impl IntoIterator<Item = I::Input>,
+) -> Vec<Option<I::Output>> {
+    inputs
+        .into_iter()
+        .map(|input| indicator.update(input))
+        .collect()
+}
+
+/// Close prices of `candles`.
+pub fn closes(candles: &[Candle]) -> Vec<f64> {
+    candles.iter().map(|c| c.close).collect()
+}
+
+/// Timestamped samples from feeder values, skipping those without a price.
+pub fn price_samples(infos: &[PriceInfo]) -> Vec<Sample> {
+    infos
+        .iter()
+        .filter_map(|info| {
+            info.price.map(|price| Sample {
+                timestamp: info.updated_at,
+                price,
+            })
+        })
+        .collect()
+}
+
+pub fn sma(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    series(Sma::new(period), values.iter().copied())
+}
+
+pub fn ema(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    series(Ema::new(period), values.iter().copied())
+}
+
+pub fn rsi(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    series(Rsi::new(period), values.iter().copied())
+}
+
+pub fn bollinger(values: &[f64], period: usize, k: f64) -> Vec<Option<BollingerBands>> {
+    series(Bollinger::new(period, k), values.iter().copied())
+}
+
+pub fn atr(candles: &[Candle], period: usize) -> Vec<Option<f64>> {
+    series(Atr::new(period), candles.iter().copied())
+}
+
+pub fn twap(samples: &[Sample], window_secs: u64) -> Vec<Option<f64>> {
+    series(Twap::new(window_secs), samples.iter().copied())
+}
+
+pub fn realized_volatility(values: &[f64], period: usize) -> Vec<Option<f64>> {
+    series(RealizedVolatility::new(period), values.iter().copied())
+}
+
+/// Simple moving average of the last `period` values.
+#[derive(Debug, Clone)]
+pub struct Sma {
+    period: usize,
+    window: VecDeque<f64>,
+    sum: f64,
+}
+
+impl Sma {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            window: VecDeque::new(),
+            sum: 0.0,
+        }
+    }
+}
+
+impl Indicator for Sma {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.window.push_back(value);
+        self.sum += value;
+        if self.window.len() > self.period {
+            self.sum -= self.window.pop_front().unwrap_or_default();
+        }
+        (self.window.len() == self.period).then(|| self.sum / self.period as f64)
+    }
+}
+
+/// Exponential moving average, seeded with the SMA of the first `period` values.
+#[derive(Debug, Clone)]
+pub struct Ema {
+    alpha: f64,
+    seed: Sma,
+    value: Option<f64>,
+}
+
+impl Ema {
+    pub fn new(period: usize) -> Self {
+        let period = period.max(1);
+        Self {
+            alpha: 2.0 / (period as f64 + 1.0),
+            seed: Sma::new(period),
+            value: None,
+        }
+    }
+}
+
+impl Indicator for Ema {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        self.value = match self.value {
+            Some(previous) => Some(previous + self.alpha * (value - previous)),
+            None => self.seed.update(value),
+        };
+        self.value
+    }
+}
+
+/// Wilder's relative strength index (0-100).
+#[derive(Debug, Clone)]
+pub struct Rsi {
+    period: usize,
+    previous: Option<f64>,
+    changes: usize,
+    avg_gain: f64,
+    avg_loss: f64,
+}
+
+impl Rsi {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous: None,
+            changes: 0,
+            avg_gain: 0.0,
+            avg_loss: 0.0,
+        }
+    }
+}
+
+impl Indicator for Rsi {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let previous = self.previous.replace(value)?;
+        let change = value - previous;
+        let (gain, loss) = (change.max(0.0), (-change).max(0.0));
+        let period = self.period as f64;
+
+        self.changes += 1;
+        if self.changes <= self.period {
+            // Seed with plain averages of the first `period` changes.
+            self.avg_gain += gain / period;
+            self.avg_loss += loss / period;
+            if self.changes < self.period {
+                return None;
+            }
+        } else {
+            self.avg_gain = (self.avg_gain * (period - 1.0) + gain) / period;
+            self.avg_loss = (self.avg_loss * (period - 1.0) + loss) / period;
+        }
+
+        if self.avg_loss == 0.0 {
+            return Some(100.0);
+        }
+        Some(100.0 - 100.0 / (1.0 + self.avg_gain / self.avg_loss))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub lower: f64,
+    pub middle: f64,
+    pub upper: f64,
+}
+
+/// SMA ± `k` population standard deviations over `period` values.
+#[derive(Debug, Clone)]
+pub struct Bollinger {
+    period: usize,
+    k: f64,
+    window: VecDeque<f64>,
+}
+
+impl Bollinger {
+    pub fn new(period: usize, k: f64) -> Self {
+        Self {
+            period: period.max(1),
+            k,
+            window: VecDeque::new(),
+        }
+    }
+}
+
+impl Indicator for Bollinger {
+    type Input = f64;
+    type Output = BollingerBands;
+
+    fn update(&mut self, value: f64) -> Option<BollingerBands> {
+        self.window.push_back(value);
+        if self.window.len() > self.period {
+            self.window.pop_front();
+        }
+        if self.window.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let middle = self.window.iter().sum::<f64>() / n;
+        let variance = self
+            .window
+            .iter()
+            .map(|v| (v - middle).powi(2))
+            .sum::<f64>()
+            / n;
+        let width = self.k * variance.sqrt();
+        Some(BollingerBands {
+            lower: middle - width,
+            middle,
+            upper: middle + width,
+        })
+    }
+}
+
+/// Wilder's average true range over candles.
+#[derive(Debug, Clone)]
+pub struct Atr {
+    period: usize,
+    previous_close: Option<f64>,
+    count: usize,
+    value: f64,
+}
+
+impl Atr {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(1),
+            previous_close: None,
+            count: 0,
+            value: 0.0,
+        }
+    }
+}
+
+impl Indicator for Atr {
+    type Input = Candle;
+    type Output = f64;
+
+    fn update(&mut self, candle: Candle) -> Option<f64> {
+        let true_range = match self.previous_close.replace(candle.close) {
+            Some(close) => (candle.high - candle.low)
+                .max((candle.high - close).abs())
+                .max((candle.low - close).abs()),
+            None => candle.high - candle.low,
+        };
+        let period = self.period as f64;
+
+        self.count += 1;
+        if self.count <= self.period {
+            self.value += true_range / period;
+            return (self.count == self.period).then_some(self.value);
+        }
+        self.value = (self.value * (period - 1.0) + true_range) / period;
+        Some(self.value)
+    }
+}
+
+/// Time-weighted average price over the trailing `window_secs`.
+///
+/// Each price is weighted by how long it stood until the next sample, so irregular
+/// polling does not skew the average towards bursts of ticks.
+#[derive(Debug, Clone)]
+pub struct Twap {
+    window_secs: u64,
+    samples: VecDeque<Sample>,
+}
+
+impl Twap {
+    pub fn new(window_secs: u64) -> Self {
+        Self {
+            window_secs,
+            samples: VecDeque::new(),
+        }
+    }
+}
+
+impl Indicator for Twap {
+    type Input = Sample;
+    type Output = f64;
+
+    fn update(&mut self, sample: Sample) -> Option<f64> {
+        self.samples.push_back(sample);
+        let window_start = sample.timestamp.saturating_sub(self.window_secs);
+        // Keep the last sample at or before the window start: it covers the window's beginning.
+        while self.samples.len() > 1 && self.samples[1].timestamp <= window_start {
+            self.samples.pop_front();
+        }
+
+        let mut weighted = 0.0;
+        let mut total = 0.0;
+        for (current, next) in self.samples.iter().zip(self.samples.iter().skip(1)) {
+            let from = current.timestamp.max(window_start);
+            let duration = next.timestamp.saturating_sub(from) as f64;
+            weighted += current.price * duration;
+            total += duration;
+        }
+        if total == 0.0 {
+            return Some(sample.price);
+        }
+        Some(weighted / total)
+    }
+}
+
+/// Sample standard deviation of log returns over the last `period` returns
+/// (per sampling interval; see `annualize`).
+#[derive(Debug, Clone)]
+pub struct RealizedVolatility {
+    period: usize,
+    previous: Option<f64>,
+    returns: VecDeque<f64>,
+}
+
+impl RealizedVolatility {
+    pub fn new(period: usize) -> Self {
+        Self {
+            period: period.max(2),
+            previous: None,
+            returns: VecDeque::new(),
+        }
+    }
+}
+
+impl Indicator for RealizedVolatility {
+    type Input = f64;
+    type Output = f64;
+
+    fn update(&mut self, value: f64) -> Option<f64> {
+        let previous = self.previous.replace(value)?;
+        if previous <= 0.0 || value <= 0.0 {
+            return None;
+        }
+        self.returns.push_back((value / previous).ln());
+        if self.returns.len() > self.period {
+            self.returns.pop_front();
+        }
+        if self.returns.len() < self.period {
+            return None;
+        }
+        let n = self.period as f64;
+        let mean = self.returns.iter().sum::<f64>() / n;
+        let variance = self.returns.iter().map(|r| (r - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        Some(variance.sqrt())
+    }
+}
+
+/// Scales a per-interval volatility to a yearly one (e.g. `365.0` for daily candles).
+pub fn annualize(volatility: f64, periods_per_year: f64) -> f64 {
+    volatility * periods_per_year.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: Option<f64>, expected: f64, tolerance: f64) {
+        let actual = actual.expect("indicator should be warmed up");
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "expected {expected}, got {actual}"
+        );
+    }
+
+    // StockCharts' 10-day EMA example.
+    const EMA_CLOSES: [f64; 20] = [
+        22.27, 22.19, 22.08, 22.17, 22.18, 22.13, 22.23, 22.43, 22.24, 22.29, 22.15, 22.39, 22.38,
+        22.61, 23.36, 24.05, 23.75, 23.83, 23.95, 23.63,
+    ];
+
+    // Close series from the classic 14-day RSI walkthrough.
+    const RSI_CLOSES: [f64; 20] = [
+        44.34, 44.09, 44.15, 43.61, 44.33, 44.83, 45.10, 45.42, 45.84, 46.08, 45.89, 46.03, 45.61,
+        46.28, 46.28, 46.00, 46.03, 46.41, 46.22, 45.64,
+    ];
+
+    #[test]
+    fn test_sma_and_ema_reference_values() {
+        let sma = sma(&EMA_CLOSES, 10);
+        assert!(sma[8].is_none());
+        assert_close(sma[9], 22.22, 0.01);
+
+        let ema = ema(&EMA_CLOSES, 10);
+        assert_close(ema[9], 22.22, 0.01);
+        assert_close(ema[10], 22.21, 0.01);
+        assert_close(ema[14], 22.52, 0.01);
+        assert_close(ema[19], 23.34, 0.01);
+    }
+
+    #[test]
+    fn test_rsi_reference_values() {
+        let rsi = rsi(&RSI_CLOSES, 14);
+        assert!(rsi[13].is_none());
+        // First 14 changes: gains sum to 3.34, losses to 1.40.
+        let (avg_gain, avg_loss) = (3.34 / 14.0, 1.40 / 14.0);
+        assert_close(rsi[14], 100.0 - 100.0 / (1.0 + avg_gain / avg_loss), 1e-9);
+        assert_close(rsi[14], 70.46, 0.01);
+        // Then Wilder smoothing; 46.28 -> 46.00 is a 0.28 loss.
+        let (avg_gain, avg_loss) = (avg_gain * 13.0 / 14.0, (avg_loss * 13.0 + 0.28) / 14.0);
+        assert_close(rsi[15], 100.0 - 100.0 / (1.0 + avg_gain / avg_loss), 1e-9);
+        assert_close(rsi[15], 66.25, 0.01);
+    }
+
+    #[test]
+    fn test_bollinger_bands() {
+        // Population standard deviation of this series is exactly 2.
+        let bands = bollinger(&[2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0], 8, 2.0);
+        assert_eq!(
+            bands[7],
+            Some(BollingerBands {
+                lower: 1.0,
+                middle: 5.0,
+                upper: 9.0,
+            })
+        );
+    }
+
+    #[test]
+    fn test_atr_uses_previous_close() {
+        let candle = |high: f64, low: f64, close: f64| Candle {
+            start: 0,
+            open: close,
+            high,
+            low,
+            close,
+            samples: 1,
+        };
+        // True ranges: 2, 3 (gap up from 11 to a 14 high), 2 -> seed (2 + 3 + 2) / 3.
+        let candles = [
+            candle(12.0, 10.0, 11.0),
+            candle(14.0, 12.0, 13.0),
+            candle(14.0, 12.0, 12.5),
+            candle(16.0, 13.0, 15.0),
+        ];
+        let atr = atr(&candles, 3);
+        assert!(atr[1].is_none());
+        assert_close(atr[2], 7.0 / 3.0, 1e-9);
+        // Wilder smoothing with TR = 3.5 (16 - 12.5).
+        assert_close(atr[3], (7.0 / 3.0 * 2.0 + 3.5) / 3.0, 1e-9);
+    }
+
+    #[test]
+    fn test_twap_weights_by_time() {
+        let samples = [
+            Sample {
+                timestamp: 0,
+                price: 100.0,
+            },
+            Sample {
+                timestamp: 30,
+                price: 110.0,
+            },
+            Sample {
+                timestamp: 40,
+                price: 90.0,
+            },
+            Sample {
+                timestamp: 60,
+                price: 95.0,
+            },
+        ];
+        let twap = twap(&samples, 60);
+        assert_eq!(twap[0], Some(100.0));
+        // 100 for 30s, 110 for 10s, 90 for 20s.
+        assert_close(
+            twap[3],
+            (100.0 * 30.0 + 110.0 * 10.0 + 90.0 * 20.0) / 60.0,
+            1e-9,
+        );
+    }
+
+    #[test]
+    fn test_realized_volatility_and_streaming_form() {
+        let vol = realized_volatility(&[100.0, 110.0, 99.0], 2);
+        // ln(1.1) and ln(0.9), sample standard deviation.
+        let (a, b) = (1.1f64.ln(), 0.9f64.ln());
+        let mean = (a + b) / 2.0;
+        let expected = ((a - mean).powi(2) + (b - mean).powi(2)).sqrt();
+        assert_close(vol[2], expected, 1e-12);
+        assert_close(Some(annualize(0.01, 365.0)), 0.191049, 1e-6);
+
+        // Streaming updates match the batch form tick by tick.
+        let mut streaming = Ema::new(10);
+        let from_ticks: Vec<_> = EMA_CLOSES.iter().map(|c| streaming.update(*c)).collect();
+        assert_eq!(from_ticks, ema(&EMA_CLOSES, 10));
+
+        let infos = [
+            PriceInfo {
+                price: Some(1.0),
+                ui_price: "1".to_string(),
+                updated_at: 10,
+            },
+            PriceInfo::default(),
+        ];
+        assert_eq!(
+            price_samples(&infos),
+            vec![Sample {
+                timestamp: 10,
+                price: 1.0
+            }]
+        );
+    }
+}
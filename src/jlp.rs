@@ -0,0 +1,278 @@
+//! JLP pool stats: what backs JLP and how each custody is used.
+
+use crate::error::{parse_f64, Result};
+use crate::token_registry::{self, Token};
+use serde::{Deserialize, Serialize};
+
+/// Id used in parse errors for pool-level fields.
+const JLP_POOL_ID: &str = "JLP";
+
+/// Raw `/jlp-info` response; amounts are USD and rates percentages, as strings.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct JlpInfoResponse {
+    pub aum_usd: String,
+    pub jlp_price_usd: String,
+    pub jlp_supply: String,
+    pub fee_apr_percent: String,
+    pub custodies: Vec<CustodyInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CustodyInfo {
+    pub mint: String,
+    pub aum_usd: String,
+    pub current_weight_percent: String,
+    pub target_weight_percent: String,
+    pub utilization_percent: String,
+    pub hourly_borrow_rate_percent: String,
+    pub long_open_interest_usd: String,
+    pub short_open_interest_usd: String,
+}
+
+/// The JLP pool. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct JlpPool {
+    pub aum_usd: f64,
+    /// AUM per JLP token.
+    pub virtual_price: f64,
+    pub supply: f64,
+    /// Annualized fees paid to JLP holders, in percent.
+    pub fee_apr_percent: f64,
+    pub custodies: Vec<Custody>,
+}
+
+/// One asset backing JLP (SOL, ETH, WBTC, USDC, USDT). Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Custody {
+    pub mint: String,
+    pub aum_usd: f64,
+    pub weight_percent: f64,
+    pub target_weight_percent: f64,
+    /// Share of the custody lent out to traders, in percent.
+    pub utilization_percent: f64,
+    pub hourly_borrow_rate_percent: f64,
+    pub long_open_interest_usd: f64,
+    pub short_open_interest_usd: f64,
+}
+
+impl Custody {
+    /// Registry token of the custody mint, when known.
+    pub fn token(&self) -> Option<&'static Token> {
+        token_registry::get_by_address(&self.mint)
+    }
+
+    /// Percentage points the custody is above (positive) or below its target weight.
+    pub fn weight_deviation(&self) -> f64 {
+        self.weight_percent - self.target_weight_percent
+    }
+
+    /// Borrow rate over a year (hourly rate × 24 × 365), in percent.
+    pub fn annual_borrow_rate_percent(&self) -> f64 {
+        self.hourly_borrow_rate_percent * 24.0 * 365.0
+    }
+}
+
+impl JlpPool {
+    /// The custody of `symbol` (e.g. "SOL"), using registry symbols.
+    pub fn custody(&self, symbol: &str) -> Option<&Custody> {
+        self.custodies
+            .iter()
+            .find(|c| c.token().is_some_and(|t| t.symbol.as_ref() == symbol))
+    }
+
+    /// USD value of each custody backing one JLP, by mint.
+    pub fn backing_per_jlp(&self) -> Vec<(String, f64)> {
+        self.custodies
+            .iter()
+            .map(|c| {
+                let per_jlp = if self.supply > 0.0 {
+                    c.aum_usd / self.supply
+                } else {
+                    0.0
+                };
+                (c.mint.clone(), per_jlp)
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<JlpInfoResponse> for JlpPool {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(response: JlpInfoResponse) -> Result<Self> {
+        Ok(Self {
+            aum_usd: parse_f64("aum_usd", &response.aum_usd, JLP_POOL_ID)?,
+            virtual_price: parse_f64("jlp_price_usd", &response.jlp_price_usd, JLP_POOL_ID)?,
+            supply: parse_f64("jlp_supply", &response.jlp_supply, JLP_POOL_ID)?,
+            fee_apr_percent: parse_f64("fee_apr_percent", &response.fee_apr_percent, JLP_POOL_ID)?,
+            custodies: response
+                .custodies
+                .iter()
+                .map(Custody::try_from)
+                .collect::<Result<_>>()?,
+        })
+    }
+}
+
+impl TryFrom<&CustodyInfo> for Custody {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(custody: &CustodyInfo) -> Result<Self> {
+        let id = &custody.mint;
+        Ok(Self {
+            mint: custody.mint.clone(),
+            aum_usd: parse_f64("aum_usd", &custody.aum_usd, id)?,
+            weight_percent: parse_f64(
+                "current_weight_percent",
+                &custody.current_weight_percent,
+                id,
+            )?,
+            target_weight_percent: parse_f64(
+                "target_weight_percent",
+                &custody.target_weight_percent,
+                id,
+            )?,
+            utilization_percent: parse_f64(
+                "utilization_percent",
+                &custody.utilization_percent,
+                id,
+            )?,
+            hourly_borrow_rate_percent: parse_f64(
+                "hourly_borrow_rate_percent",
+                &custody.hourly_borrow_rate_percent,
+                id,
+            )?,
+            long_open_interest_usd: parse_f64(
+                "long_open_interest_usd",
+                &custody.long_open_interest_usd,
+                id,
+            )?,
+            short_open_interest_usd: parse_f64(
+                "short_open_interest_usd",
+                &custody.short_open_interest_usd,
+                id,
+            )?,
+        })
+    }
+}
+
+// `/jlp-info` with SOL 1.5 points over target and USDC 1.5 points under.
+#[cfg(all(test, feature = "native"))]
+const JLP_INFO_FIXTURE: &str = r#"{
+  "aumUsd": "1200000000",
+  "jlpPriceUsd": "4.25",
+  "jlpSupply": "282352941.176471",
+  "feeAprPercent": "18.4",
+  "custodies": [
+    {
+      "mint": "So11111111111111111111111111111111111111112",
+      "aumUsd": "546000000",
+      "currentWeightPercent": "45.5",
+      "targetWeightPercent": "44",
+      "utilizationPercent": "62.5",
+      "hourlyBorrowRatePercent": "0.0031",
+      "longOpenInterestUsd": "310000000",
+      "shortOpenInterestUsd": "95000000"
+    },
+    {
+      "mint": "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs",
+      "aumUsd": "120000000",
+      "currentWeightPercent": "10",
+      "targetWeightPercent": "10",
+      "utilizationPercent": "48.1",
+      "hourlyBorrowRatePercent": "0.0021",
+      "longOpenInterestUsd": "52000000",
+      "shortOpenInterestUsd": "18000000"
+    },
+    {
+      "mint": "3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh",
+      "aumUsd": "132000000",
+      "currentWeightPercent": "11",
+      "targetWeightPercent": "11",
+      "utilizationPercent": "51.3",
+      "hourlyBorrowRatePercent": "0.0024",
+      "longOpenInterestUsd": "61000000",
+      "shortOpenInterestUsd": "22000000"
+    },
+    {
+      "mint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+      "aumUsd": "294000000",
+      "currentWeightPercent": "24.5",
+      "targetWeightPercent": "26",
+      "utilizationPercent": "40.2",
+      "hourlyBorrowRatePercent": "0.0018",
+      "longOpenInterestUsd": "0",
+      "shortOpenInterestUsd": "0"
+    },
+    {
+      "mint": "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+      "aumUsd": "108000000",
+      "currentWeightPercent": "9",
+      "targetWeightPercent": "9",
+      "utilizationPercent": "35.7",
+      "hourlyBorrowRatePercent": "0.0015",
+      "longOpenInterestUsd": "0",
+      "shortOpenInterestUsd": "0"
+    }
+  ]
+}"#;
+
+#[cfg(all(feature = "log-native", feature = "native"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::JupSdkError;
+    use crate::fetcher::Fetcher;
+    use crate::perps::{PerpsFetcher, PERPS_API_BASE};
+    use crate::transport::{MockResponse, MockTransport};
+    use std::sync::Arc;
+
+    fn fetcher_with(body: &serde_json::Value) -> PerpsFetcher {
+        let url = format!("{}/jlp-info", PERPS_API_BASE);
+        let mock = Arc::new(MockTransport::new().on(&url, MockResponse::json(200, body)));
+        PerpsFetcher::with_fetcher(Fetcher::new().with_transport(mock))
+    }
+
+    #[tokio::test]
+    async fn test_fetch_jlp_pool() -> Result<()> {
+        let fixture: serde_json::Value = serde_json::from_str(JLP_INFO_FIXTURE).unwrap();
+        let pool = fetcher_with(&fixture).fetch_jlp_pool().await?;
+
+        assert_eq!(pool.aum_usd, 1_200_000_000.0);
+        assert_eq!(pool.virtual_price, 4.25);
+        assert_eq!(pool.fee_apr_percent, 18.4);
+        assert_eq!(pool.custodies.len(), 5);
+
+        let sol = pool.custody("SOL").unwrap();
+        assert_eq!(sol.weight_deviation(), 1.5);
+        assert_eq!(sol.utilization_percent, 62.5);
+        assert_eq!(
+            (sol.long_open_interest_usd, sol.short_open_interest_usd),
+            (310_000_000.0, 95_000_000.0)
+        );
+        assert!((sol.annual_borrow_rate_percent() - 27.156).abs() < 1e-9);
+        assert_eq!(pool.custody("USDC").unwrap().weight_deviation(), -1.5);
+        assert_eq!(pool.custody("WBTC").unwrap().token().unwrap().decimals, 8);
+
+        // Backing per JLP adds up to the virtual price.
+        let backing: f64 = pool.backing_per_jlp().iter().map(|(_, usd)| usd).sum();
+        assert!((backing - pool.virtual_price).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_jlp_pool_parse_error_names_custody() {
+        let mut fixture: serde_json::Value = serde_json::from_str(JLP_INFO_FIXTURE).unwrap();
+        fixture["custodies"][1]["utilizationPercent"] = "".into();
+
+        match fetcher_with(&fixture).fetch_jlp_pool().await.unwrap_err() {
+            JupSdkError::Parse { field, id, .. } => {
+                assert_eq!(field, "utilization_percent");
+                assert_eq!(id, "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+}
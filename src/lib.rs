@@ -1,9 +1,25 @@
+pub mod alerts;
+pub mod borrow_fees;
+pub mod cache;
 pub mod compat;
+pub mod error;
 pub mod feeder;
 pub mod fetcher;
 pub mod formatter;
+pub mod history;
+pub mod indicators;
+pub mod jlp;
 pub mod perps;
+pub mod portfolio;
+pub mod position_tracker;
 pub mod prices;
+pub mod rate_limit;
 pub mod ray;
+pub mod risk;
+pub mod simulator;
+pub mod storage;
+pub mod swap;
 pub mod time;
 pub mod token_registry;
+pub mod trades;
+pub mod transport;
@@ -1,7 +1,16 @@
 use super::fetcher::{Fetcher, RetrySettings};
-use anyhow::{anyhow, Result};
-use serde::{Deserialize, Serialize};
+use crate::error::{parse_decimal, parse_f64, Result};
+use crate::jlp::{JlpInfoResponse, JlpPool};
+use crate::rate_limit::EndpointFamily;
+use crate::time::get_unix_timestamp;
+use crate::token_registry::{self, Token};
+use crate::trades::{
+    Page, Period, PositionRequest, PositionRequestData, RealizedPnlSummary, Trade, TradeData,
+};
+use rust_decimal::Decimal;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::str::FromStr;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use strum::EnumString;
 use strum_macros::Display;
 
@@ -46,7 +55,7 @@ pub struct PositionData {
     pub value: String,
 }
 
-fn deserialize_side<'de, D>(deserializer: D) -> Result<Side, D::Error>
+fn deserialize_side<'de, D>(deserializer: D) -> std::result::Result<Side, D::Error>
 where
     D: serde::Deserializer<'de>,
 {
@@ -72,6 +81,56 @@ pub struct TpslRequest {
     pub trigger_price_usd: String,
 }
 
+/// Market stats from `/pool-info?mint=`, for the custody of the market `mint`.
+/// Rates and utilization are percentages; borrow rates are per hour.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PoolInfo {
+    pub long_available_liquidity: String,
+    pub long_borrow_rate_percent: String,
+    pub long_utilization_percent: String,
+    pub short_available_liquidity: String,
+    pub short_borrow_rate_percent: String,
+    pub short_utilization_percent: String,
+    pub open_fee_percent: String,
+    pub max_request_execution_sec: String,
+    pub max_price_impact_fee_percent: String,
+}
+
+impl PoolInfo {
+    /// Hourly borrow rate in percent for positions on `side`.
+    pub fn borrow_rate_percent(&self, side: &Side, mint: &str) -> Result<f64> {
+        match side {
+            Side::Long => parse_f64(
+                "long_borrow_rate_percent",
+                &self.long_borrow_rate_percent,
+                mint,
+            ),
+            Side::Short => parse_f64(
+                "short_borrow_rate_percent",
+                &self.short_borrow_rate_percent,
+                mint,
+            ),
+        }
+    }
+
+    /// Utilization in percent of the custody positions on `side` borrow from.
+    pub fn utilization_percent(&self, side: &Side, mint: &str) -> Result<f64> {
+        match side {
+            Side::Long => parse_f64(
+                "long_utilization_percent",
+                &self.long_utilization_percent,
+                mint,
+            ),
+            Side::Short => parse_f64(
+                "short_utilization_percent",
+                &self.short_utilization_percent,
+                mint,
+            ),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct PositionPNLs {
     pub total_pnl_usd: f64,
@@ -79,6 +138,62 @@ pub struct PositionPNLs {
     pub position_pnls: Vec<PositionPNL>,
 }
 
+impl PositionPNLs {
+    /// Sums PnL over `positions`; the total percentage is weighted by position value.
+    pub fn from_positions(positions: &[PositionData]) -> Result<Self> {
+        let mut total_pnl_usd = 0.0;
+        let mut total_value = 0.0; // Needed to calculate weighted average PNL percentage
+        let mut position_pnls = Vec::new();
+
+        for position in positions {
+            let pnl_usd = parse_f64(
+                "pnl_after_fees_usd",
+                &position.pnl_after_fees_usd,
+                &position.position_pubkey,
+            )?;
+            let pnl_percent = parse_f64(
+                "pnl_change_pct_after_fees",
+                &position.pnl_change_pct_after_fees,
+                &position.position_pubkey,
+            )?;
+            // Parse value for weighted percentage calculation
+            let value_usd = parse_f64("value", &position.value, &position.position_pubkey)?;
+
+            total_pnl_usd += pnl_usd;
+            // Accumulate value for weighted average calculation
+            if value_usd > 0.0 {
+                // Avoid division by zero or issues with negative value if possible
+                total_value += value_usd;
+            }
+
+            position_pnls.push(PositionPNL {
+                position_pubkey: position.position_pubkey.clone(),
+                side: position.side.clone(),
+                pnl_usd,
+                pnl_percent, // Keep individual percent if needed
+            });
+        }
+
+        // Calculate a weighted average PNL percentage if total value is positive
+        let total_pnl_percent_avg = if total_value > 0.0 {
+            // Calculate weighted average: sum(pnl_usd_i) / sum(value_usd_i) * 100
+            // Or approximate by (total_pnl_usd / (total_value - total_pnl_usd)) * 100 if 'value' is collateral+pnl
+            // Simpler: total_pnl_usd / total_value * 100 if 'value' is current total value including PNL
+            // The direct sum `total_pnl_percent` is usually not meaningful. Let's calculate a weighted average.
+            (total_pnl_usd / total_value) * 100.0 // Assuming 'value' is the total current value
+        } else {
+            0.0 // Avoid division by zero
+        };
+
+        Ok(Self {
+            total_pnl_usd,
+            // Use the calculated weighted average percentage instead of summing percentages
+            total_pnl_percent: total_pnl_percent_avg,
+            position_pnls,
+        })
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, PartialEq)]
 pub struct PositionPNL {
     pub position_pubkey: String,
@@ -95,80 +210,260 @@ pub enum Side {
     Short,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+/// A TP/SL trigger request attached to a position.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TriggerRequest {
+    pub position_request_pubkey: String,
+    /// Mint paid out when the request executes.
+    pub desired_mint: String,
+    pub trigger_price: f64,
+    pub trigger_price_usd: f64,
+}
+
+impl TryFrom<TpslRequest> for TriggerRequest {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(request: TpslRequest) -> Result<Self> {
+        let id = &request.position_request_pubkey;
+        Ok(Self {
+            trigger_price: parse_f64("trigger_price", &request.trigger_price, id)?,
+            trigger_price_usd: parse_f64("trigger_price_usd", &request.trigger_price_usd, id)?,
+            position_request_pubkey: request.position_request_pubkey,
+            desired_mint: request.desired_mint,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub struct PerpsPosition {
-    pub side: Side,                // Position side: Long or Short
-    pub market_mint: String,       // So11111111111111111111111111111111111111112
-    pub collateral_mint: String,   // EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v
-    pub entry_price: f64,          // Entry price of the position
-    pub leverage: f64,             // Leverage used for the position
-    pub liquidation_price: f64,    // Liquidation price of the position
-    pub pnl_after_fees_usd: f64,   // Profit/loss after fees in USD
-    pub value: f64,                // Current position value in USD
-    pub target_price: Option<f64>, // Current target price in USD
-    pub stop_loss: Option<f64>,    // Current stop loss in USD
+    pub side: Side,                                  // Position side: Long or Short
+    pub market_mint: String,                         // So11111111111111111111111111111111111111112
+    pub collateral_mint: String,                     // EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v
+    pub entry_price: f64,                            // Entry price of the position
+    pub leverage: f64,                               // Leverage used for the position
+    pub liquidation_price: f64,                      // Liquidation price of the position
+    pub pnl_after_fees_usd: f64,                     // Profit/loss after fees in USD
+    pub value: f64,                                  // Current position value in USD
+    pub target_price: Option<f64>,                   // Current target price in USD
+    pub stop_loss: Option<f64>,                      // Current stop loss in USD
+    pub position_pubkey: String,                     // 5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx
+    pub size: f64,                                   // Position size in USD
+    pub size_token_amount: f64,                      // Position size in market token units
+    pub collateral: f64,                             // Collateral in USD
+    pub pnl_before_fees_usd: f64,                    // Profit/loss before fees in USD
+    pub pnl_change_pct_before_fees: f64,             // PnL before fees in percent of collateral
+    pub pnl_change_pct_after_fees: f64,              // PnL after fees in percent of collateral
+    pub borrow_fees_usd: f64,                        // Borrow fees accrued so far in USD
+    pub open_fees_usd: f64,                          // Fees paid to open in USD
+    pub close_fees_usd: f64,                         // Fees to close in USD
+    pub total_fees_usd: f64,                         // Borrow, open and close fees in USD
+    pub take_profit_request: Option<TriggerRequest>, // TP request behind `target_price`
+    pub stop_loss_request: Option<TriggerRequest>,   // SL request behind `stop_loss`
+    pub created_time: i64,                           // Unix seconds
+    pub updated_time: i64,                           // Unix seconds
 }
 
-impl From<PositionData> for PerpsPosition {
-    fn from(position: PositionData) -> Self {
-        let entry_price = position.entry_price.parse().unwrap_or(0.0);
-        let leverage = position.leverage.parse().unwrap_or(1.1); // 1x leverage as fallback
-        let liquidation_price = position.liquidation_price.parse().unwrap_or(0.0);
-        let pnl_after_fees_usd = position.pnl_after_fees_usd.parse().unwrap_or(0.0);
-        let value = position.value.parse().unwrap_or(0.0);
-        let target_price = position
+impl TryFrom<PositionData> for PerpsPosition {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(position: PositionData) -> Result<Self> {
+        let id = &position.position_pubkey;
+        let take_profit_request = position
             .tpsl_requests
             .tp
-            .unwrap_or_default()
-            .trigger_price_usd
-            .parse()
-            .ok();
-        let stop_loss = position
+            .map(TriggerRequest::try_from)
+            .transpose()?;
+        let stop_loss_request = position
             .tpsl_requests
             .sl
-            .unwrap_or_default()
-            .trigger_price_usd
-            .parse()
-            .ok();
+            .map(TriggerRequest::try_from)
+            .transpose()?;
 
-        PerpsPosition {
+        Ok(PerpsPosition {
+            entry_price: parse_f64("entry_price", &position.entry_price, id)?,
+            leverage: parse_f64("leverage", &position.leverage, id)?,
+            liquidation_price: parse_f64("liquidation_price", &position.liquidation_price, id)?,
+            pnl_after_fees_usd: parse_f64("pnl_after_fees_usd", &position.pnl_after_fees_usd, id)?,
+            value: parse_f64("value", &position.value, id)?,
+            target_price: take_profit_request.as_ref().map(|r| r.trigger_price_usd),
+            stop_loss: stop_loss_request.as_ref().map(|r| r.trigger_price_usd),
+            size: parse_f64("size", &position.size, id)?,
+            size_token_amount: parse_f64("size_token_amount", &position.size_token_amount, id)?,
+            collateral: parse_f64("collateral", &position.collateral, id)?,
+            pnl_before_fees_usd: parse_f64(
+                "pnl_before_fees_usd",
+                &position.pnl_before_fees_usd,
+                id,
+            )?,
+            pnl_change_pct_before_fees: parse_f64(
+                "pnl_change_pct_before_fees",
+                &position.pnl_change_pct_before_fees,
+                id,
+            )?,
+            pnl_change_pct_after_fees: parse_f64(
+                "pnl_change_pct_after_fees",
+                &position.pnl_change_pct_after_fees,
+                id,
+            )?,
+            borrow_fees_usd: parse_f64("borrow_fees_usd", &position.borrow_fees_usd, id)?,
+            open_fees_usd: parse_f64("open_fees_usd", &position.open_fees_usd, id)?,
+            close_fees_usd: parse_f64("close_fees_usd", &position.close_fees_usd, id)?,
+            total_fees_usd: parse_f64("total_fees_usd", &position.total_fees_usd, id)?,
+            take_profit_request,
+            stop_loss_request,
+            created_time: position.created_time,
+            updated_time: position.updated_time,
             side: position.side,
             market_mint: position.market_mint,
             collateral_mint: position.collateral_mint,
-            entry_price,
-            leverage,
-            liquidation_price,
-            pnl_after_fees_usd,
-            value,
-            target_price,
-            stop_loss,
-        }
+            position_pubkey: position.position_pubkey,
+        })
     }
 }
 
-const PERPS_API_BASE: &str = "https://perps-api.jup.ag/v1";
+impl PerpsPosition {
+    /// Registry token of the market mint, when known.
+    pub fn market_token(&self) -> Option<&'static Token> {
+        token_registry::get_by_address(&self.market_mint)
+    }
+
+    /// Registry token of the collateral mint, when known.
+    pub fn collateral_token(&self) -> Option<&'static Token> {
+        token_registry::get_by_address(&self.collateral_mint)
+    }
+
+    pub fn created_at(&self) -> SystemTime {
+        unix_time(self.created_time)
+    }
+
+    pub fn updated_at(&self) -> SystemTime {
+        unix_time(self.updated_time)
+    }
+
+    /// Time since the position was opened.
+    pub fn age(&self) -> Duration {
+        self.age_at(get_unix_timestamp() as i64)
+    }
+
+    /// Time between opening and `now` (unix seconds); zero if `now` is earlier.
+    pub fn age_at(&self, now: i64) -> Duration {
+        Duration::from_secs(now.saturating_sub(self.created_time).max(0) as u64)
+    }
+}
+
+fn unix_time(seconds: i64) -> SystemTime {
+    UNIX_EPOCH + Duration::from_secs(seconds.max(0) as u64)
+}
+
+/// A position with exact decimal amounts, as reported by the API. Amounts are in USD
+/// except `size_token_amount`, which is in market token units.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct DecimalPerpsPosition {
+    pub position_pubkey: String,
+    pub side: Side,
+    pub market_mint: String,
+    pub collateral_mint: String,
+    pub entry_price: Decimal,
+    pub leverage: Decimal,
+    pub liquidation_price: Decimal,
+    pub size: Decimal,
+    pub size_token_amount: Decimal,
+    pub collateral: Decimal,
+    pub value: Decimal,
+    pub pnl_before_fees_usd: Decimal,
+    pub pnl_after_fees_usd: Decimal,
+    pub borrow_fees_usd: Decimal,
+    pub open_fees_usd: Decimal,
+    pub close_fees_usd: Decimal,
+    pub total_fees_usd: Decimal,
+    /// Take profit trigger price, if one is set.
+    pub target_price: Option<Decimal>,
+    /// Stop loss trigger price, if one is set.
+    pub stop_loss: Option<Decimal>,
+}
+
+impl TryFrom<PositionData> for DecimalPerpsPosition {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(position: PositionData) -> Result<Self> {
+        let id = &position.position_pubkey;
+        let trigger = |field: &str, request: &Option<TpslRequest>| {
+            request
+                .as_ref()
+                .map(|r| parse_decimal(field, &r.trigger_price_usd, id))
+                .transpose()
+        };
+
+        Ok(Self {
+            entry_price: parse_decimal("entry_price", &position.entry_price, id)?,
+            leverage: parse_decimal("leverage", &position.leverage, id)?,
+            liquidation_price: parse_decimal("liquidation_price", &position.liquidation_price, id)?,
+            size: parse_decimal("size", &position.size, id)?,
+            size_token_amount: parse_decimal("size_token_amount", &position.size_token_amount, id)?,
+            collateral: parse_decimal("collateral", &position.collateral, id)?,
+            value: parse_decimal("value", &position.value, id)?,
+            pnl_before_fees_usd: parse_decimal(
+                "pnl_before_fees_usd",
+                &position.pnl_before_fees_usd,
+                id,
+            )?,
+            pnl_after_fees_usd: parse_decimal(
+                "pnl_after_fees_usd",
+                &position.pnl_after_fees_usd,
+                id,
+            )?,
+            borrow_fees_usd: parse_decimal("borrow_fees_usd", &position.borrow_fees_usd, id)?,
+            open_fees_usd: parse_decimal("open_fees_usd", &position.open_fees_usd, id)?,
+            close_fees_usd: parse_decimal("close_fees_usd", &position.close_fees_usd, id)?,
+            total_fees_usd: parse_decimal("total_fees_usd", &position.total_fees_usd, id)?,
+            target_price: trigger("tp_trigger_price_usd", &position.tpsl_requests.tp)?,
+            stop_loss: trigger("sl_trigger_price_usd", &position.tpsl_requests.sl)?,
+            position_pubkey: position.position_pubkey,
+            side: position.side,
+            market_mint: position.market_mint,
+            collateral_mint: position.collateral_mint,
+        })
+    }
+}
+
+pub(crate) const PERPS_API_BASE: &str = "https://perps-api.jup.ag/v1";
+
+/// Default number of records requested per page from the history endpoints.
+pub const DEFAULT_HISTORY_PAGE_SIZE: usize = 100;
 
 pub struct PerpsFetcher {
     // Use the generic Fetcher
     fetcher: Fetcher,
+    history_page_size: usize,
 }
 
 impl PerpsFetcher {
     /// Creates a new PerpsFetcher with default retry settings.
     pub fn new() -> Self {
-        Self {
-            fetcher: Fetcher::new(), // Or Fetcher::default()
-        }
+        Self::with_fetcher(Fetcher::new()) // Or Fetcher::default()
     }
 
     /// Creates a new PerpsFetcher with custom retry settings.
     pub fn with_settings(settings: RetrySettings) -> Self {
+        Self::with_fetcher(Fetcher::with_settings(settings))
+    }
+
+    /// Creates a new PerpsFetcher on top of an existing `Fetcher` (custom transport, etc.).
+    /// Requests are tagged as `EndpointFamily::Perps` for rate limiting.
+    pub fn with_fetcher(fetcher: Fetcher) -> Self {
         Self {
-            fetcher: Fetcher::with_settings(settings),
+            fetcher: fetcher.with_endpoint_family(EndpointFamily::Perps),
+            history_page_size: DEFAULT_HISTORY_PAGE_SIZE,
         }
     }
 
+    /// Sets how many records each history request asks for.
+    pub fn with_history_page_size(mut self, history_page_size: usize) -> Self {
+        self.history_page_size = history_page_size.max(1);
+        self
+    }
+
     /// Fetches positions from the Jupiter Perps API with retry logic.
     pub async fn fetch_positions(&self, wallet_address: &str) -> Result<PositionsResponse> {
         let url = format!(
@@ -176,104 +471,138 @@ impl PerpsFetcher {
             PERPS_API_BASE, wallet_address
         );
 
-        // Use the fetcher's fetch_with_retry method
+        // Use the fetcher's fetch_with_retry method (errors carry the URL, which names the wallet)
         self.fetcher
             .fetch_with_retry::<PositionsResponse>(&url)
             .await
-            .map_err(|e| {
-                anyhow!(
-                    "Failed to fetch positions for wallet {}: {}",
-                    wallet_address,
-                    e
-                )
-            })
     }
 
-    /// Fetches positions, calculates aggregate PNL, and formats the result.
-    pub async fn fetch_positions_pnl_and_format(
+    /// Fetches a wallet's positions with exact amounts; fails on the first
+    /// position with a malformed amount.
+    pub async fn fetch_decimal_positions(
         &self,
         wallet_address: &str,
-    ) -> Result<PositionPNLs> {
-        // This now uses the fetch_positions method which includes retries
-        let positions_response = self.fetch_positions(wallet_address).await?;
+    ) -> Result<Vec<DecimalPerpsPosition>> {
+        self.fetch_positions(wallet_address)
+            .await?
+            .data_list
+            .into_iter()
+            .map(DecimalPerpsPosition::try_from)
+            .collect()
+    }
 
-        let mut total_pnl_usd = 0.0;
-        let mut total_value = 0.0; // Needed to calculate weighted average PNL percentage
-        let mut position_pnls = Vec::new();
+    /// Fetches borrow rates and utilization for the market `mint` (e.g. SOL).
+    pub async fn fetch_pool_info(&self, mint: &str) -> Result<PoolInfo> {
+        let url = format!("{}/pool-info?mint={}", PERPS_API_BASE, mint);
+        self.fetcher.fetch_with_retry::<PoolInfo>(&url).await
+    }
 
-        for position in positions_response.data_list {
-            let pnl_usd = position.pnl_after_fees_usd.parse::<f64>().map_err(|e| {
-                anyhow!(
-                    "Failed to parse pnl_after_fees_usd '{}' for position {}: {}",
-                    position.pnl_after_fees_usd,
-                    position.position_pubkey,
-                    e
-                )
-            })?;
-            let pnl_percent = position
-                .pnl_change_pct_after_fees
-                .parse::<f64>()
-                .map_err(|e| {
-                    anyhow!(
-                        "Failed to parse pnl_change_pct_after_fees '{}' for position {}: {}",
-                        position.pnl_change_pct_after_fees,
-                        position.position_pubkey,
-                        e
-                    )
-                })?;
-            // Parse value for weighted percentage calculation
-            let value_usd = position.value.parse::<f64>().map_err(|e| {
-                anyhow!(
-                    "Failed to parse value '{}' for position {}: {}",
-                    position.value,
-                    position.position_pubkey,
-                    e
-                )
-            })?;
+    /// Fetches JLP pool and custody stats.
+    pub async fn fetch_jlp_info(&self) -> Result<JlpInfoResponse> {
+        let url = format!("{}/jlp-info", PERPS_API_BASE);
+        self.fetcher.fetch_with_retry::<JlpInfoResponse>(&url).await
+    }
 
-            total_pnl_usd += pnl_usd;
-            // Accumulate value for weighted average calculation
-            if value_usd > 0.0 {
-                // Avoid division by zero or issues with negative value if possible
-                total_value += value_usd;
-            }
+    /// Fetches JLP pool and custody stats as numbers.
+    pub async fn fetch_jlp_pool(&self) -> Result<JlpPool> {
+        self.fetch_jlp_info().await.and_then(JlpPool::try_from)
+    }
 
-            position_pnls.push(PositionPNL {
-                position_pubkey: position.position_pubkey.clone(),
-                side: position.side.clone(),
-                pnl_usd,
-                pnl_percent, // Keep individual percent if needed
-            });
-        }
+    /// Fetches one page (records `start..end`) of a wallet's trades.
+    pub async fn fetch_trades_page(
+        &self,
+        wallet_address: &str,
+        start: usize,
+        end: usize,
+    ) -> Result<Page<TradeData>> {
+        let url = format!(
+            "{}/trades?walletAddress={}&start={}&end={}",
+            PERPS_API_BASE, wallet_address, start, end
+        );
+        self.fetcher.fetch_with_retry::<Page<TradeData>>(&url).await
+    }
 
-        // Calculate a weighted average PNL percentage if total value is positive
-        let total_pnl_percent_avg = if total_value > 0.0 {
-            // Calculate weighted average: sum(pnl_usd_i) / sum(value_usd_i) * 100
-            // Or approximate by (total_pnl_usd / (total_value - total_pnl_usd)) * 100 if 'value' is collateral+pnl
-            // Simpler: total_pnl_usd / total_value * 100 if 'value' is current total value including PNL
-            // The direct sum `total_pnl_percent` is usually not meaningful. Let's calculate a weighted average.
-            (total_pnl_usd / total_value) * 100.0 // Assuming 'value' is the total current value
-        } else {
-            0.0 // Avoid division by zero
-        };
+    /// Fetches a wallet's whole trade history, page by page.
+    pub async fn fetch_trades(&self, wallet_address: &str) -> Result<Vec<Trade>> {
+        let url = format!("{}/trades?walletAddress={}", PERPS_API_BASE, wallet_address);
+        self.fetch_all_pages::<TradeData>(&url)
+            .await?
+            .into_iter()
+            .map(Trade::try_from)
+            .collect()
+    }
 
-        Ok(PositionPNLs {
-            total_pnl_usd,
-            // Use the calculated weighted average percentage instead of summing percentages
-            total_pnl_percent: total_pnl_percent_avg,
-            position_pnls,
-        })
+    /// Fetches all of a wallet's position requests (market orders and TP/SL/limit
+    /// triggers, executed or not), page by page.
+    pub async fn fetch_position_requests(
+        &self,
+        wallet_address: &str,
+    ) -> Result<Vec<PositionRequest>> {
+        let url = format!(
+            "{}/position-requests?walletAddress={}",
+            PERPS_API_BASE, wallet_address
+        );
+        self.fetch_all_pages::<PositionRequestData>(&url)
+            .await?
+            .into_iter()
+            .map(PositionRequest::try_from)
+            .collect()
+    }
+
+    /// Fetches the trade history and sums realized PnL per market and `period`.
+    pub async fn fetch_realized_pnl_summary(
+        &self,
+        wallet_address: &str,
+        period: Period,
+    ) -> Result<RealizedPnlSummary> {
+        let trades = self.fetch_trades(wallet_address).await?;
+        Ok(RealizedPnlSummary::new(&trades, period))
     }
 
-    /// Fetches positions and converts them into a simplified `PerpsPosition` format.
+    // Requests `start`/`end` windows of `history_page_size` until `count` records
+    // (or an empty page) have been received.
+    async fn fetch_all_pages<T: DeserializeOwned + Send + 'static>(
+        &self,
+        url: &str,
+    ) -> Result<Vec<T>> {
+        let mut records = Vec::new();
+        loop {
+            let start = records.len();
+            let page_url = format!(
+                "{}&start={}&end={}",
+                url,
+                start,
+                start + self.history_page_size
+            );
+            let page = self.fetcher.fetch_with_retry::<Page<T>>(&page_url).await?;
+            let received = page.data_list.len();
+            records.extend(page.data_list);
+            if received == 0 || records.len() >= page.count.max(0) as usize {
+                return Ok(records);
+            }
+        }
+    }
+
+    /// Fetches positions, calculates aggregate PNL, and formats the result.
+    pub async fn fetch_positions_pnl_and_format(
+        &self,
+        wallet_address: &str,
+    ) -> Result<PositionPNLs> {
+        // This now uses the fetch_positions method which includes retries
+        let positions_response = self.fetch_positions(wallet_address).await?;
+        PositionPNLs::from_positions(&positions_response.data_list)
+    }
+
+    /// Fetches positions and converts them into `PerpsPosition`s; fails on the first
+    /// position with a malformed amount.
     pub async fn fetch_perps_positions(&self, wallet_address: &str) -> Result<Vec<PerpsPosition>> {
         // This now uses the fetch_positions method which includes retries
         let positions_response = self.fetch_positions(wallet_address).await?;
-        Ok(positions_response
+        positions_response
             .data_list
             .into_iter()
-            .map(PerpsPosition::from) // Note: Consider handling potential errors from `from` if it were changed to return Result
-            .collect())
+            .map(PerpsPosition::try_from)
+            .collect()
     }
 }
 
@@ -284,10 +613,141 @@ impl Default for PerpsFetcher {
     }
 }
 
+// Two SOL positions (a 10x long with TP/SL and a 10x short) at a 150 USD mark price,
+// shaped like a `/positions?showTpslRequests=true` response.
+#[cfg(test)]
+pub(crate) const POSITIONS_FIXTURE: &str = r#"{
+  "count": 2,
+  "dataList": [
+    {
+      "borrowFees": "0.5",
+      "borrowFeesUsd": "0.5",
+      "closeFees": "0.6",
+      "closeFeesUsd": "0.6",
+      "collateral": "100",
+      "collateralMint": "So11111111111111111111111111111111111111112",
+      "createdTime": 1735689600,
+      "entryPrice": "140",
+      "leverage": "10",
+      "liquidationPrice": "126.434",
+      "marketMint": "So11111111111111111111111111111111111111112",
+      "openFees": "0.6",
+      "openFeesUsd": "0.6",
+      "pnlAfterFees": "70.328571",
+      "pnlAfterFeesUsd": "70.328571",
+      "pnlBeforeFees": "71.428571",
+      "pnlBeforeFeesUsd": "71.428571",
+      "pnlChangePctAfterFees": "70.33",
+      "pnlChangePctBeforeFees": "71.43",
+      "positionPubkey": "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx",
+      "side": "long",
+      "size": "1000",
+      "sizeTokenAmount": "7.142857",
+      "totalFees": "1.7",
+      "totalFeesUsd": "1.7",
+      "tpslRequests": {
+        "tp": {
+          "desiredMint": "So11111111111111111111111111111111111111112",
+          "positionRequestPubkey": "7TpQk2pFHcLTsX8ax4dVdGxD3dzJxkXE6tq5jXCEbR9n",
+          "triggerPrice": "180",
+          "triggerPriceUsd": "180"
+        },
+        "sl": {
+          "desiredMint": "So11111111111111111111111111111111111111112",
+          "positionRequestPubkey": "9SLa3FzGz1uY4kQ8cE2mV6hN5rJbW7tXyPdLoK3sAqM1",
+          "triggerPrice": "130",
+          "triggerPriceUsd": "130"
+        }
+      },
+      "updatedTime": 1735776000,
+      "value": "170.328571"
+    },
+    {
+      "borrowFees": "0.2",
+      "borrowFeesUsd": "0.2",
+      "closeFees": "0.3",
+      "closeFeesUsd": "0.3",
+      "collateral": "50",
+      "collateralMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+      "createdTime": 1735700000,
+      "entryPrice": "160",
+      "leverage": "10",
+      "liquidationPrice": "175.52",
+      "marketMint": "So11111111111111111111111111111111111111112",
+      "openFees": "0.3",
+      "openFeesUsd": "0.3",
+      "pnlAfterFees": "30.75",
+      "pnlAfterFeesUsd": "30.75",
+      "pnlBeforeFees": "31.25",
+      "pnlBeforeFeesUsd": "31.25",
+      "pnlChangePctAfterFees": "61.5",
+      "pnlChangePctBeforeFees": "62.5",
+      "positionPubkey": "3Hq8yPz6GdQ4u1NfWkLrT2sVbE9cXmA7oJiK5nD8eRtU",
+      "side": "short",
+      "size": "500",
+      "sizeTokenAmount": "3.125",
+      "totalFees": "0.8",
+      "totalFeesUsd": "0.8",
+      "tpslRequests": {},
+      "updatedTime": 1735780000,
+      "value": "80.75"
+    }
+  ]
+}"#;
+
+// Wallet the mocked perps responses in tests are served for.
+#[cfg(all(test, feature = "native"))]
+pub(crate) const MOCK_WALLET: &str = "MockWa11et1111111111111111111111111111111111";
+
+// URL `fetch_positions` requests for `wallet`.
+#[cfg(all(test, feature = "native"))]
+pub(crate) fn positions_url(wallet: &str) -> String {
+    format!(
+        "{}/positions?walletAddress={}&showTpslRequests=true",
+        PERPS_API_BASE, wallet
+    )
+}
+
+// A `PerpsFetcher` on a mock transport serving `positions` for `wallet`; push more
+// routes onto the returned mock as needed.
+#[cfg(all(test, feature = "native"))]
+pub(crate) fn mock_positions_fetcher(
+    wallet: &str,
+    positions: &serde_json::Value,
+) -> (
+    std::sync::Arc<crate::transport::MockTransport>,
+    PerpsFetcher,
+) {
+    use crate::transport::{MockResponse, MockTransport};
+
+    let mock = std::sync::Arc::new(
+        MockTransport::new().on(&positions_url(wallet), MockResponse::json(200, positions)),
+    );
+    let perps_fetcher = PerpsFetcher::with_fetcher(Fetcher::new().with_transport(mock.clone()));
+    (mock, perps_fetcher)
+}
+
+// `/pool-info?mint=` for SOL: 0.0012%/h for longs, 0.0025%/h for shorts.
+#[cfg(all(test, feature = "native"))]
+pub(crate) const POOL_INFO_FIXTURE: &str = r#"{
+  "longAvailableLiquidity": "1523401.25",
+  "longBorrowRatePercent": "0.0012",
+  "longUtilizationPercent": "41.2",
+  "shortAvailableLiquidity": "8812345.5",
+  "shortBorrowRatePercent": "0.0025",
+  "shortUtilizationPercent": "63.8",
+  "openFeePercent": "0.06",
+  "maxRequestExecutionSec": "45",
+  "maxPriceImpactFeePercent": "0.1"
+}"#;
+
 #[cfg(all(feature = "log-native", feature = "native"))]
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::JupSdkError;
+    use crate::transport::{MockResponse, MockTransport};
+    use std::sync::Arc;
     use std::time::Duration;
 
     fn setup() {
@@ -423,4 +883,245 @@ mod tests {
             );
         }
     }
+
+    #[tokio::test]
+    async fn test_fetch_positions_pnl_with_mock_transport() -> Result<()> {
+        setup();
+        let fixture: serde_json::Value =
+            serde_json::from_str(POSITIONS_FIXTURE).expect("valid fixture");
+        let (mock, perps_fetcher) = mock_positions_fetcher(MOCK_WALLET, &fixture);
+
+        let pnl_summary = perps_fetcher
+            .fetch_positions_pnl_and_format(MOCK_WALLET)
+            .await?;
+
+        assert_eq!(mock.request_count(&positions_url(MOCK_WALLET)), 1);
+        assert_eq!(pnl_summary.position_pnls.len(), 2);
+        assert_eq!(pnl_summary.position_pnls[1].side, Side::Short);
+        assert!((pnl_summary.total_pnl_usd - 101.078571).abs() < 1e-6);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_trade_history_pages() -> Result<()> {
+        setup();
+        let wallet_address = MOCK_WALLET;
+        let trades_url = |start: usize, end: usize| {
+            format!(
+                "{}/trades?walletAddress={}&start={}&end={}",
+                PERPS_API_BASE, wallet_address, start, end
+            )
+        };
+        let requests_url = format!(
+            "{}/position-requests?walletAddress={}&start=0&end=2",
+            PERPS_API_BASE, wallet_address
+        );
+        let trades: Vec<serde_json::Value> =
+            serde_json::from_str(crate::trades::TRADES_FIXTURE).expect("valid fixture");
+        let request = serde_json::json!({
+            "positionRequestPubkey": "7TpQk2pFHcLTsX8ax4dVdGxD3dzJxkXE6tq5jXCEbR9n",
+            "positionPubkey": "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx",
+            "marketMint": "So11111111111111111111111111111111111111112",
+            "side": "long",
+            "requestType": "Trigger",
+            "requestChange": "Decrease",
+            "sizeUsdDelta": "1000",
+            "collateralUsdDelta": "0",
+            "triggerPrice": "180",
+            "executed": false,
+            "txHash": null,
+            "createdTime": 1735689600
+        });
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    &trades_url(0, 2),
+                    MockResponse::json(
+                        200,
+                        &serde_json::json!({"count": 3, "dataList": &trades[..2]}),
+                    ),
+                )
+                .on(
+                    &trades_url(2, 4),
+                    MockResponse::json(
+                        200,
+                        &serde_json::json!({"count": 3, "dataList": &trades[2..]}),
+                    ),
+                )
+                .on(
+                    &requests_url,
+                    MockResponse::json(
+                        200,
+                        &serde_json::json!({"count": 1, "dataList": [request]}),
+                    ),
+                ),
+        );
+        let perps_fetcher = PerpsFetcher::with_fetcher(Fetcher::new().with_transport(mock.clone()))
+            .with_history_page_size(2);
+
+        let trades = perps_fetcher.fetch_trades(wallet_address).await?;
+        assert_eq!(trades.len(), 3);
+        assert_eq!(mock.request_count(&trades_url(2, 4)), 1);
+
+        let summary = perps_fetcher
+            .fetch_realized_pnl_summary(wallet_address, Period::Year)
+            .await?;
+        assert_eq!(summary.by_period["2025"].trades, 2);
+
+        let requests = perps_fetcher
+            .fetch_position_requests(wallet_address)
+            .await?;
+        assert_eq!(requests.len(), 1);
+        assert_eq!(
+            requests[0].request_type,
+            crate::trades::RequestType::Trigger
+        );
+        assert_eq!(requests[0].trigger_price, Some(180.0));
+        assert_eq!(requests[0].tx_signature, None);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_positions_pnl_parse_error() {
+        setup();
+        let mut fixture: serde_json::Value =
+            serde_json::from_str(POSITIONS_FIXTURE).expect("valid fixture");
+        fixture["dataList"][1]["pnlAfterFeesUsd"] = "n/a".into();
+        let (_, perps_fetcher) = mock_positions_fetcher(MOCK_WALLET, &fixture);
+
+        let err = perps_fetcher
+            .fetch_positions_pnl_and_format(MOCK_WALLET)
+            .await
+            .unwrap_err();
+
+        match err {
+            JupSdkError::Parse {
+                field, value, id, ..
+            } => {
+                assert_eq!(field, "pnl_after_fees_usd");
+                assert_eq!(value, "n/a");
+                assert_eq!(id, "3Hq8yPz6GdQ4u1NfWkLrT2sVbE9cXmA7oJiK5nD8eRtU");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_decimal_position_conversion() {
+        let mut fixture: PositionsResponse =
+            serde_json::from_str(POSITIONS_FIXTURE).expect("valid fixture");
+        let mut long = fixture.data_list.remove(0);
+        long.size_token_amount = "7.142857142857142857".to_string();
+
+        let position = DecimalPerpsPosition::try_from(long.clone()).unwrap();
+        assert_eq!(
+            position.size_token_amount.to_string(),
+            "7.142857142857142857"
+        );
+        assert_eq!(position.entry_price, Decimal::from(140));
+        assert_eq!(position.target_price, Some(Decimal::from(180)));
+        assert_eq!(
+            position.total_fees_usd,
+            position.borrow_fees_usd + position.open_fees_usd + position.close_fees_usd
+        );
+
+        // A malformed entry price is an error, not a 0.0 entry.
+        long.entry_price = "".to_string();
+        match DecimalPerpsPosition::try_from(long).unwrap_err() {
+            JupSdkError::Parse { field, id, .. } => {
+                assert_eq!(field, "entry_price");
+                assert_eq!(id, "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_perps_position_exposes_position_data() {
+        let fixture: PositionsResponse =
+            serde_json::from_str(POSITIONS_FIXTURE).expect("valid fixture");
+        let [long, short]: [PerpsPosition; 2] = fixture
+            .data_list
+            .into_iter()
+            .map(|p| PerpsPosition::try_from(p).unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        assert_eq!(
+            long.position_pubkey,
+            "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx"
+        );
+        assert_eq!((long.size, long.collateral), (1000.0, 100.0));
+        assert_eq!(long.size_token_amount, 7.142857);
+        assert_eq!(long.pnl_before_fees_usd, 71.428571);
+        assert_eq!(long.total_fees_usd, 1.7);
+        let sl = long.stop_loss_request.as_ref().unwrap();
+        assert_eq!(
+            sl.position_request_pubkey,
+            "9SLa3FzGz1uY4kQ8cE2mV6hN5rJbW7tXyPdLoK3sAqM1"
+        );
+        assert_eq!((sl.trigger_price_usd, long.stop_loss), (130.0, Some(130.0)));
+        assert_eq!(short.take_profit_request, None);
+
+        assert_eq!(long.market_token().unwrap().symbol.as_ref(), "SOL");
+        assert_eq!(short.collateral_token().unwrap().symbol.as_ref(), "USDC");
+        assert_eq!(
+            long.updated_at().duration_since(long.created_at()).unwrap(),
+            Duration::from_secs(86_400)
+        );
+        assert_eq!(long.age_at(1735689600 + 90), Duration::from_secs(90));
+        assert_eq!(long.age_at(0), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_perps_position_parse_errors() {
+        let fixture: PositionsResponse =
+            serde_json::from_str(POSITIONS_FIXTURE).expect("valid fixture");
+        let long = fixture.data_list[0].clone();
+
+        // Malformed leverage and entry prices used to become 1.1 and 0.0.
+        type Corrupt = fn(&mut PositionData);
+        let cases: [(&str, Corrupt); 3] = [
+            ("leverage", |p| p.leverage = "n/a".to_string()),
+            ("entry_price", |p| p.entry_price = String::new()),
+            ("value", |p| p.value = "NaN".to_string()),
+        ];
+        for (expected, corrupt) in cases {
+            let mut position = long.clone();
+            corrupt(&mut position);
+            match PerpsPosition::try_from(position).unwrap_err() {
+                JupSdkError::Parse { field, id, .. } => {
+                    assert_eq!(field, expected);
+                    assert_eq!(id, "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx");
+                }
+                other => panic!("Expected Parse error, got {:?}", other),
+            }
+        }
+
+        let mut bad_size = long.clone();
+        bad_size.size = "n/a".to_string();
+        match PerpsPosition::try_from(bad_size).unwrap_err() {
+            JupSdkError::Parse { field, id, .. } => {
+                assert_eq!(field, "size");
+                assert_eq!(id, "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+
+        let mut bad_stop_loss = long;
+        bad_stop_loss
+            .tpsl_requests
+            .sl
+            .as_mut()
+            .unwrap()
+            .trigger_price_usd = String::new();
+        match PerpsPosition::try_from(bad_stop_loss).unwrap_err() {
+            JupSdkError::Parse { field, id, .. } => {
+                assert_eq!(field, "trigger_price_usd");
+                assert_eq!(id, "9SLa3FzGz1uY4kQ8cE2mV6hN5rJbW7tXyPdLoK3sAqM1");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
 }
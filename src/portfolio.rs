@@ -0,0 +1,222 @@
+use crate::error::{parse_f64, JupSdkError, Result};
+use crate::perps::{PerpsFetcher, PositionData, PositionPNLs, Side};
+use futures_util::{stream, StreamExt};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// Default number of wallets fetched at the same time.
+pub const DEFAULT_MAX_CONCURRENT_WALLETS: usize = 4;
+
+/// A position together with the wallet holding it.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WalletPosition {
+    pub wallet: String,
+    pub position: PositionData,
+}
+
+/// Open size in USD per side of one market, across wallets.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct MarketExposure {
+    pub long_usd: f64,
+    pub short_usd: f64,
+}
+
+impl MarketExposure {
+    /// Long minus short size.
+    pub fn net_usd(&self) -> f64 {
+        self.long_usd - self.short_usd
+    }
+}
+
+/// Perps positions of many wallets. Amounts are in USD.
+#[derive(Debug, Default)]
+pub struct Portfolio {
+    /// PnL per wallet that was fetched successfully.
+    pub wallets: BTreeMap<String, PositionPNLs>,
+    pub positions: Vec<WalletPosition>,
+    /// Wallets whose positions couldn't be fetched or parsed; excluded from the totals.
+    pub errors: HashMap<String, Arc<JupSdkError>>,
+    pub total_pnl_usd: f64,
+    /// `total_pnl_usd` relative to the total position value, as in `PositionPNLs`.
+    pub total_pnl_percent: f64,
+    /// Sum of position sizes.
+    pub total_notional_usd: f64,
+    pub total_value_usd: f64,
+    pub total_fees_usd: f64,
+    /// Keyed by market mint.
+    pub exposure: BTreeMap<String, MarketExposure>,
+}
+
+impl Portfolio {
+    /// Every wallet was fetched.
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+
+    /// Adds one wallet's positions; on a parse error nothing is added.
+    pub fn add_wallet(&mut self, wallet: &str, positions: Vec<PositionData>) -> Result<()> {
+        let pnls = PositionPNLs::from_positions(&positions)?;
+
+        let mut notional = 0.0;
+        let mut value = 0.0;
+        let mut fees = 0.0;
+        let mut exposure: Vec<(&str, &Side, f64)> = Vec::with_capacity(positions.len());
+        for position in &positions {
+            let id = &position.position_pubkey;
+            let size = parse_f64("size", &position.size, id)?;
+            let position_value = parse_f64("value", &position.value, id)?;
+            if position_value > 0.0 {
+                value += position_value;
+            }
+            notional += size;
+            fees += parse_f64("total_fees_usd", &position.total_fees_usd, id)?;
+            exposure.push((&position.market_mint, &position.side, size));
+        }
+
+        for (market_mint, side, size) in exposure {
+            let market = self.exposure.entry(market_mint.to_string()).or_default();
+            match side {
+                Side::Long => market.long_usd += size,
+                Side::Short => market.short_usd += size,
+            }
+        }
+        self.total_pnl_usd += pnls.total_pnl_usd;
+        self.total_notional_usd += notional;
+        self.total_value_usd += value;
+        self.total_fees_usd += fees;
+        self.total_pnl_percent = if self.total_value_usd > 0.0 {
+            self.total_pnl_usd / self.total_value_usd * 100.0
+        } else {
+            0.0
+        };
+        self.wallets.insert(wallet.to_string(), pnls);
+        self.positions
+            .extend(positions.into_iter().map(|position| WalletPosition {
+                wallet: wallet.to_string(),
+                position,
+            }));
+        Ok(())
+    }
+}
+
+/// Fetches and aggregates the perps positions of many wallets.
+pub struct PortfolioFetcher {
+    perps: PerpsFetcher,
+    max_concurrent_requests: usize,
+}
+
+impl Default for PortfolioFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PortfolioFetcher {
+    pub fn new() -> Self {
+        Self {
+            perps: PerpsFetcher::new(),
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_WALLETS,
+        }
+    }
+
+    pub fn with_perps_fetcher(mut self, perps: PerpsFetcher) -> Self {
+        self.perps = perps;
+        self
+    }
+
+    /// Limits how many wallets are fetched at the same time.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent_requests: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent_requests.max(1);
+        self
+    }
+
+    /// Fetches every wallet (duplicates once). A wallet that fails is reported in
+    /// `Portfolio::errors` instead of failing the whole portfolio.
+    pub async fn fetch_portfolio(&self, wallets: &[&str]) -> Portfolio {
+        let mut unique: Vec<&str> = Vec::with_capacity(wallets.len());
+        for wallet in wallets {
+            if !unique.contains(wallet) {
+                unique.push(wallet);
+            }
+        }
+
+        let results: Vec<(&str, Result<Vec<PositionData>>)> = stream::iter(unique)
+            .map(|wallet| async move {
+                let positions = self
+                    .perps
+                    .fetch_positions(wallet)
+                    .await
+                    .map(|response| response.data_list);
+                (wallet, positions)
+            })
+            .buffer_unordered(self.max_concurrent_requests)
+            .collect()
+            .await;
+
+        let mut portfolio = Portfolio::default();
+        for (wallet, positions) in results {
+            if let Err(error) = positions.and_then(|p| portfolio.add_wallet(wallet, p)) {
+                portfolio.errors.insert(wallet.to_string(), Arc::new(error));
+            }
+        }
+        // Keep the order stable regardless of which request finished first.
+        portfolio.positions.sort_by(|a, b| a.wallet.cmp(&b.wallet));
+        portfolio
+    }
+}
+
+#[cfg(all(feature = "log-native", feature = "native"))]
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perps::{mock_positions_fetcher, positions_url, POSITIONS_FIXTURE};
+    use crate::transport::MockResponse;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    #[tokio::test]
+    async fn test_fetch_portfolio_aggregates_and_reports_failures() {
+        let fixture: serde_json::Value = serde_json::from_str(POSITIONS_FIXTURE).unwrap();
+        let mut long_only = fixture.clone();
+        long_only["dataList"].as_array_mut().unwrap().truncate(1);
+        long_only["count"] = 1.into();
+        let mut broken = fixture.clone();
+        broken["dataList"][0]["size"] = "n/a".into();
+
+        let (mock, perps_fetcher) = mock_positions_fetcher("walletA", &fixture);
+        mock.push(
+            &positions_url("walletB"),
+            MockResponse::json(200, &long_only),
+        );
+        mock.push(&positions_url("walletC"), MockResponse::json(200, &broken));
+        mock.push(&positions_url("walletD"), MockResponse::new(404));
+        let portfolio = PortfolioFetcher::new()
+            .with_perps_fetcher(perps_fetcher)
+            .with_max_concurrent_requests(2)
+            .fetch_portfolio(&["walletA", "walletB", "walletC", "walletD", "walletA"])
+            .await;
+
+        assert_eq!(mock.request_count(&positions_url("walletA")), 1);
+        assert!(!portfolio.is_complete());
+        let mut failed: Vec<_> = portfolio.errors.keys().map(String::as_str).collect();
+        failed.sort();
+        assert_eq!(failed, vec!["walletC", "walletD"]);
+        assert!(matches!(
+            portfolio.errors["walletC"].as_ref(),
+            JupSdkError::Parse { field, .. } if field == "size"
+        ));
+
+        // walletA: long $1000 + short $500; walletB: long $1000.
+        assert_eq!(portfolio.wallets.len(), 2);
+        assert_eq!(portfolio.wallets["walletB"].position_pnls.len(), 1);
+        assert_eq!(portfolio.positions.len(), 3);
+        assert_eq!(portfolio.positions[2].wallet, "walletB");
+        assert_eq!(portfolio.total_notional_usd, 2500.0);
+        assert!((portfolio.total_fees_usd - (1.7 + 0.8 + 1.7)).abs() < 1e-9);
+        assert!((portfolio.total_pnl_usd - (70.328571 * 2.0 + 30.75)).abs() < 1e-6);
+        let sol = &portfolio.exposure[SOL];
+        assert_eq!((sol.long_usd, sol.short_usd), (2000.0, 500.0));
+        assert_eq!(sol.net_usd(), 1500.0);
+    }
+}
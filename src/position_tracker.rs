@@ -0,0 +1,459 @@
+use crate::error::{parse_f64, Result};
+use crate::perps::{PositionData, PositionsResponse, Side, TpslRequest};
+use crate::token_registry;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+use strum_macros::Display;
+
+#[derive(Display, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Trigger {
+    #[strum(serialize = "take profit")]
+    TakeProfit,
+    #[strum(serialize = "stop loss")]
+    StopLoss,
+}
+
+/// What changed about a position between two snapshots. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum PositionChange {
+    Opened {
+        size: f64,
+        collateral: f64,
+        leverage: f64,
+        entry_price: f64,
+    },
+    /// Values are the last ones seen before the position disappeared.
+    Closed {
+        size: f64,
+        pnl_after_fees_usd: f64,
+    },
+    SizeIncreased {
+        from: f64,
+        to: f64,
+    },
+    SizeDecreased {
+        from: f64,
+        to: f64,
+    },
+    CollateralChanged {
+        from: f64,
+        to: f64,
+    },
+    LeverageChanged {
+        from: f64,
+        to: f64,
+    },
+    TriggerAdded {
+        trigger: Trigger,
+        price: f64,
+    },
+    TriggerRemoved {
+        trigger: Trigger,
+        price: f64,
+    },
+    TriggerMoved {
+        trigger: Trigger,
+        from: f64,
+        to: f64,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionEvent {
+    pub position_pubkey: String,
+    pub side: Side,
+    pub market_mint: String,
+    pub change: PositionChange,
+}
+
+impl PositionEvent {
+    /// Registry symbol of the market (e.g. "SOL"), or the mint when unknown.
+    pub fn market_symbol(&self) -> String {
+        token_registry::get_by_address(&self.market_mint)
+            .map(|token| token.symbol.to_string())
+            .unwrap_or_else(|| self.market_mint.clone())
+    }
+}
+
+impl fmt::Display for PositionEvent {
+    /// e.g. "SOL long closed with $12.30 PnL", "SOL short take profit moved from $180.00 to $170.00".
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} ", self.market_symbol(), self.side)?;
+        match &self.change {
+            PositionChange::Opened { size, leverage, .. } => {
+                write!(f, "opened: ${:.2} at {:.2}x", size, leverage)
+            }
+            PositionChange::Closed {
+                pnl_after_fees_usd, ..
+            } => write!(f, "closed with ${:.2} PnL", pnl_after_fees_usd),
+            PositionChange::SizeIncreased { from, to } => {
+                write!(f, "size increased from ${:.2} to ${:.2}", from, to)
+            }
+            PositionChange::SizeDecreased { from, to } => {
+                write!(f, "size decreased from ${:.2} to ${:.2}", from, to)
+            }
+            PositionChange::CollateralChanged { from, to } => {
+                write!(f, "collateral changed from ${:.2} to ${:.2}", from, to)
+            }
+            PositionChange::LeverageChanged { from, to } => {
+                write!(f, "leverage changed from {:.2}x to {:.2}x", from, to)
+            }
+            PositionChange::TriggerAdded { trigger, price } => {
+                write!(f, "{} set at ${:.2}", trigger, price)
+            }
+            PositionChange::TriggerRemoved { trigger, price } => {
+                write!(f, "{} at ${:.2} removed", trigger, price)
+            }
+            PositionChange::TriggerMoved { trigger, from, to } => {
+                write!(f, "{} moved from ${:.2} to ${:.2}", trigger, from, to)
+            }
+        }
+    }
+}
+
+// A position from the last snapshot, plus the values last reported for it.
+#[derive(Debug)]
+struct Tracked {
+    position: PositionData,
+    size: f64,
+    collateral: f64,
+    leverage: f64,
+}
+
+impl Tracked {
+    fn new(position: &PositionData) -> Result<Self> {
+        let id = &position.position_pubkey;
+        Ok(Self {
+            position: position.clone(),
+            size: parse_f64("size", &position.size, id)?,
+            collateral: parse_f64("collateral", &position.collateral, id)?,
+            leverage: parse_f64("leverage", &position.leverage, id)?,
+        })
+    }
+}
+
+/// Diffs successive position snapshots of one wallet, matched by `position_pubkey`.
+///
+/// The first snapshot only sets the baseline and produces no events, so restarting a
+/// bot doesn't report every existing position as opened.
+#[derive(Debug, Default)]
+pub struct PositionTracker {
+    positions: Option<HashMap<String, Tracked>>,
+    min_change_percent: f64,
+}
+
+impl PositionTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ignores size, collateral and leverage changes smaller than this percent of the
+    /// last reported value (leverage drifts with the mark price). Defaults to 0: any
+    /// change is reported.
+    pub fn with_min_change_percent(mut self, min_change_percent: f64) -> Self {
+        self.min_change_percent = min_change_percent;
+        self
+    }
+
+    /// Positions from the last snapshot.
+    pub fn positions(&self) -> impl Iterator<Item = &PositionData> {
+        self.positions
+            .iter()
+            .flat_map(|positions| positions.values().map(|tracked| &tracked.position))
+    }
+
+    pub fn update(&mut self, response: &PositionsResponse) -> Result<Vec<PositionEvent>> {
+        self.update_positions(&response.data_list)
+    }
+
+    /// Applies a new snapshot and returns what changed since the previous one.
+    /// On a parse error the tracker keeps its previous state.
+    pub fn update_positions(&mut self, positions: &[PositionData]) -> Result<Vec<PositionEvent>> {
+        let mut events = Vec::new();
+        let mut next = HashMap::with_capacity(positions.len());
+        for position in positions {
+            let mut tracked = Tracked::new(position)?;
+            match self
+                .positions
+                .as_ref()
+                .map(|previous| previous.get(&position.position_pubkey))
+            {
+                Some(Some(before)) => self.diff(before, &mut tracked, &mut events)?,
+                Some(None) => events.push(event(
+                    position,
+                    PositionChange::Opened {
+                        size: tracked.size,
+                        collateral: tracked.collateral,
+                        leverage: tracked.leverage,
+                        entry_price: parse_f64(
+                            "entry_price",
+                            &position.entry_price,
+                            &position.position_pubkey,
+                        )?,
+                    },
+                )),
+                // First snapshot.
+                None => {}
+            }
+            next.insert(position.position_pubkey.clone(), tracked);
+        }
+
+        if let Some(previous) = &self.positions {
+            let mut closed: Vec<&PositionData> = previous
+                .values()
+                .map(|tracked| &tracked.position)
+                .filter(|before| !next.contains_key(&before.position_pubkey))
+                .collect();
+            closed.sort_by(|a, b| a.position_pubkey.cmp(&b.position_pubkey));
+            for before in closed {
+                let change = PositionChange::Closed {
+                    size: parse_f64("size", &before.size, &before.position_pubkey)?,
+                    pnl_after_fees_usd: parse_f64(
+                        "pnl_after_fees_usd",
+                        &before.pnl_after_fees_usd,
+                        &before.position_pubkey,
+                    )?,
+                };
+                events.push(event(before, change));
+            }
+        }
+
+        self.positions = Some(next);
+        Ok(events)
+    }
+
+    // Pushes the changes from `before` to `after`. Values below the change threshold
+    // are carried over in `after` so they are compared against the last reported value.
+    fn diff(
+        &self,
+        before: &Tracked,
+        after: &mut Tracked,
+        events: &mut Vec<PositionEvent>,
+    ) -> Result<()> {
+        let position = &after.position;
+        let id = &position.position_pubkey;
+
+        let (from, to) = (before.size, after.size);
+        if self.changed(from, to) {
+            let change = if to > from {
+                PositionChange::SizeIncreased { from, to }
+            } else {
+                PositionChange::SizeDecreased { from, to }
+            };
+            events.push(event(position, change));
+        } else {
+            after.size = from;
+        }
+
+        let (from, to) = (before.collateral, after.collateral);
+        if self.changed(from, to) {
+            events.push(event(
+                position,
+                PositionChange::CollateralChanged { from, to },
+            ));
+        } else {
+            after.collateral = from;
+        }
+
+        let (from, to) = (before.leverage, after.leverage);
+        if self.changed(from, to) {
+            events.push(event(
+                position,
+                PositionChange::LeverageChanged { from, to },
+            ));
+        } else {
+            after.leverage = from;
+        }
+
+        let (before_requests, after_requests) =
+            (&before.position.tpsl_requests, &position.tpsl_requests);
+        for (trigger, before_request, after_request) in [
+            (Trigger::TakeProfit, &before_requests.tp, &after_requests.tp),
+            (Trigger::StopLoss, &before_requests.sl, &after_requests.sl),
+        ] {
+            let change = match (
+                trigger_price(before_request, id)?,
+                trigger_price(after_request, id)?,
+            ) {
+                (None, Some(price)) => PositionChange::TriggerAdded { trigger, price },
+                (Some(price), None) => PositionChange::TriggerRemoved { trigger, price },
+                (Some(from), Some(to)) if from != to => {
+                    PositionChange::TriggerMoved { trigger, from, to }
+                }
+                _ => continue,
+            };
+            events.push(event(position, change));
+        }
+        Ok(())
+    }
+
+    fn changed(&self, from: f64, to: f64) -> bool {
+        if from == to {
+            return false;
+        }
+        from == 0.0 || ((to - from) / from).abs() * 100.0 > self.min_change_percent
+    }
+}
+
+fn event(position: &PositionData, change: PositionChange) -> PositionEvent {
+    PositionEvent {
+        position_pubkey: position.position_pubkey.clone(),
+        side: position.side.clone(),
+        market_mint: position.market_mint.clone(),
+        change,
+    }
+}
+
+fn trigger_price(request: &Option<TpslRequest>, id: &str) -> Result<Option<f64>> {
+    request
+        .as_ref()
+        .map(|r| parse_f64("trigger_price_usd", &r.trigger_price_usd, id))
+        .transpose()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perps::POSITIONS_FIXTURE;
+
+    const LONG: &str = "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx";
+    const SHORT: &str = "3Hq8yPz6GdQ4u1NfWkLrT2sVbE9cXmA7oJiK5nD8eRtU";
+
+    fn fixture() -> Vec<PositionData> {
+        serde_json::from_str::<PositionsResponse>(POSITIONS_FIXTURE)
+            .unwrap()
+            .data_list
+    }
+
+    fn changes(events: &[PositionEvent]) -> Vec<(&str, &PositionChange)> {
+        events
+            .iter()
+            .map(|e| (e.position_pubkey.as_str(), &e.change))
+            .collect()
+    }
+
+    #[test]
+    fn test_tracker_reports_changes_between_snapshots() {
+        let mut tracker = PositionTracker::new();
+        assert!(tracker.update_positions(&fixture()).unwrap().is_empty());
+        assert!(tracker.update_positions(&fixture()).unwrap().is_empty());
+
+        // Long: size and collateral up, TP moved, SL removed. Short: closed.
+        let mut positions = fixture();
+        let mut long = positions.remove(0);
+        long.size = "1500".to_string();
+        long.collateral = "150".to_string();
+        long.tpsl_requests.tp.as_mut().unwrap().trigger_price_usd = "190".to_string();
+        long.tpsl_requests.sl = None;
+
+        let events = tracker.update_positions(&[long.clone()]).unwrap();
+        assert_eq!(
+            changes(&events),
+            vec![
+                (
+                    LONG,
+                    &PositionChange::SizeIncreased {
+                        from: 1000.0,
+                        to: 1500.0
+                    }
+                ),
+                (
+                    LONG,
+                    &PositionChange::CollateralChanged {
+                        from: 100.0,
+                        to: 150.0
+                    }
+                ),
+                (
+                    LONG,
+                    &PositionChange::TriggerMoved {
+                        trigger: Trigger::TakeProfit,
+                        from: 180.0,
+                        to: 190.0
+                    }
+                ),
+                (
+                    LONG,
+                    &PositionChange::TriggerRemoved {
+                        trigger: Trigger::StopLoss,
+                        price: 130.0
+                    }
+                ),
+                (
+                    SHORT,
+                    &PositionChange::Closed {
+                        size: 500.0,
+                        pnl_after_fees_usd: 30.75
+                    }
+                ),
+            ]
+        );
+        assert_eq!(events[4].to_string(), "SOL short closed with $30.75 PnL");
+        assert_eq!(tracker.positions().count(), 1);
+
+        // Re-opened short, SL back on the long.
+        long.tpsl_requests.sl = fixture()[0].tpsl_requests.sl.clone();
+        let events = tracker
+            .update_positions(&[long, fixture().remove(1)])
+            .unwrap();
+        assert_eq!(
+            changes(&events),
+            vec![
+                (
+                    LONG,
+                    &PositionChange::TriggerAdded {
+                        trigger: Trigger::StopLoss,
+                        price: 130.0
+                    }
+                ),
+                (
+                    SHORT,
+                    &PositionChange::Opened {
+                        size: 500.0,
+                        collateral: 50.0,
+                        leverage: 10.0,
+                        entry_price: 160.0
+                    }
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_tracker_ignores_small_changes_and_keeps_state_on_error() {
+        let mut tracker = PositionTracker::new().with_min_change_percent(1.0);
+        tracker.update_positions(&fixture()).unwrap();
+
+        let mut positions = fixture();
+        positions[0].leverage = "10.05".to_string();
+        assert!(tracker.update_positions(&positions).unwrap().is_empty());
+
+        positions[0].leverage = "10.08".to_string();
+        assert!(tracker.update_positions(&positions).unwrap().is_empty());
+
+        // Compared with the last reported 10x, not the previous snapshot.
+        positions[0].leverage = "10.2".to_string();
+        let events = tracker.update_positions(&positions).unwrap();
+        assert_eq!(
+            events[0].change,
+            PositionChange::LeverageChanged {
+                from: 10.0,
+                to: 10.2
+            }
+        );
+
+        positions[1].size = "n/a".to_string();
+        assert!(tracker.update_positions(&positions).is_err());
+        assert_eq!(
+            tracker
+                .positions()
+                .find(|p| p.position_pubkey == SHORT)
+                .unwrap()
+                .size,
+            "500"
+        );
+    }
+}
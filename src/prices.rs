@@ -1,13 +1,19 @@
-use anyhow::{anyhow, Result};
+use futures_util::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use strum::AsRefStr;
 use strum_macros::{Display, EnumString};
 
 use crate::{
-    feeder::{PriceInfo, TokenOrPairAddress, TokenOrPairPriceInfo, TokenPriceInfo},
+    error::{parse_f64, JupSdkError, NotFoundKind, Result},
+    feeder::{
+        PairPriceInfo, PairPriceMethod, PriceInfo, TokenOrPairAddress, TokenOrPairPriceInfo,
+        TokenPriceInfo,
+    },
     fetcher::{Fetcher, RetrySettings},
     formatter::{format_price, format_price_result},
+    rate_limit::EndpointFamily,
     time::get_unix_timestamp,
     token_registry::Token,
 };
@@ -38,23 +44,119 @@ pub enum MainTokenSymbol {
     JLP,
     JUP,
     USDC,
+    USDT,
+    ETH,
+    WBTC,
     #[allow(non_camel_case_types)]
     JupSOL,
     #[allow(non_camel_case_types)]
     SOL_PERPS,
 }
 
+/// How much Jupiter trusts a derived price (`showExtraInfo=true` on Price API v2).
+#[derive(
+    Display, EnumString, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "lowercase")]
+#[strum(serialize_all = "lowercase")]
+pub enum ConfidenceLevel {
+    Low,
+    Medium,
+    High,
+}
+
+// Extra-info prices arrive as strings (sometimes numbers); both map to `f64`.
+mod f64_string_opt {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber {
+        String(String),
+        Number(f64),
+    }
+
+    pub fn serialize<S: Serializer>(value: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(v) => serializer.serialize_str(&v.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<f64>, D::Error> {
+        match Option::<StringOrNumber>::deserialize(deserializer)? {
+            Some(StringOrNumber::String(s)) => {
+                s.parse().map(Some).map_err(serde::de::Error::custom)
+            }
+            Some(StringOrNumber::Number(n)) => Ok(Some(n)),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Prices of the last Jupiter buy/sell of the token (unix seconds).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LastSwappedPrice {
+    pub last_jupiter_sell_at: Option<u64>,
+    #[serde(default, with = "f64_string_opt")]
+    pub last_jupiter_sell_price: Option<f64>,
+    pub last_jupiter_buy_at: Option<u64>,
+    #[serde(default, with = "f64_string_opt")]
+    pub last_jupiter_buy_price: Option<f64>,
+}
+
+/// Prices currently quoted for buying and selling the token (unix seconds).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuotedPrice {
+    #[serde(default, with = "f64_string_opt")]
+    pub buy_price: Option<f64>,
+    pub buy_at: Option<u64>,
+    #[serde(default, with = "f64_string_opt")]
+    pub sell_price: Option<f64>,
+    pub sell_at: Option<u64>,
+}
+
+/// Price impact ratio keyed by trade size in USD (e.g. `10`, `100`, `1000`).
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct DepthImpact {
+    pub depth: BTreeMap<u64, f64>,
+    pub timestamp: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PriceDepth {
+    pub buy_price_impact_ratio: Option<DepthImpact>,
+    pub sell_price_impact_ratio: Option<DepthImpact>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+struct ExtraInfo {
+    last_swapped_price: Option<LastSwappedPrice>,
+    quoted_price: Option<QuotedPrice>,
+    confidence_level: Option<ConfidenceLevel>,
+    depth: Option<PriceDepth>,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
 struct TokenData {
     price: String,
     #[serde(rename = "type")]
     price_type: String,
+    extra_info: Option<ExtraInfo>,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PriceResponse {
-    data: HashMap<String, TokenData>,
+    // Unknown tokens come back as `null`.
+    data: HashMap<String, Option<TokenData>>,
     time_taken: f64,
 }
 
@@ -62,68 +164,335 @@ impl PriceResponse {
     fn into_price_map(self) -> Result<HashMap<String, f64>> {
         self.data
             .into_iter()
+            .filter_map(|(address, data)| data.map(|data| (address, data)))
             .map(|(address, data)| {
-                data.price
-                    .parse::<f64>()
-                    .map(|price| (address, price))
-                    .map_err(|e| anyhow!("Failed to parse price: {}", e))
+                parse_f64("price", &data.price, &address).map(|price| (address, price))
             })
             .collect()
     }
+
+    fn into_quotes(self) -> Result<HashMap<String, PriceQuote>> {
+        self.data
+            .into_iter()
+            .filter_map(|(address, data)| data.map(|data| (address, data)))
+            .map(|(address, data)| {
+                let price = parse_f64("price", &data.price, &address)?;
+                let extra = data.extra_info.unwrap_or_default();
+                let quote = PriceQuote {
+                    id: address.clone(),
+                    price,
+                    price_type: Some(data.price_type),
+                    confidence_level: extra.confidence_level,
+                    last_swapped: extra.last_swapped_price,
+                    quoted: extra.quoted_price,
+                    depth: extra.depth,
+                    ..Default::default()
+                };
+                Ok((address, quote))
+            })
+            .collect()
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+struct TokenDataV3 {
+    usd_price: f64,
+    block_id: Option<u64>,
+    decimals: Option<u8>,
+    price_change_24h: Option<f64>,
+}
+
+// Price API v3 returns a bare map of mint -> price, omitting (or nulling) unknown mints.
+type PriceResponseV3 = HashMap<String, Option<TokenDataV3>>;
+
+/// A USD price with whatever extra detail the price API returned.
+///
+/// v2 (`showExtraInfo=true`) fills `price_type`, `confidence_level`, `last_swapped`,
+/// `quoted` and `depth`; v3 fills `block_id`, `decimals` and `price_change_24h`.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PriceQuote {
+    pub id: String,
+    pub price: f64,
+    pub price_type: Option<String>,
+    pub confidence_level: Option<ConfidenceLevel>,
+    pub last_swapped: Option<LastSwappedPrice>,
+    pub quoted: Option<QuotedPrice>,
+    pub depth: Option<PriceDepth>,
+    pub block_id: Option<u64>,
+    pub decimals: Option<u8>,
+    pub price_change_24h: Option<f64>,
+}
+
+impl PriceQuote {
+    /// Whether the quote is at least `min` confident.
+    /// v3 quotes carry no level (v3 omits unreliable prices instead) and always pass.
+    pub fn meets_confidence(&self, min: ConfidenceLevel) -> bool {
+        self.confidence_level.is_none_or(|level| level >= min)
+    }
+}
+
+#[derive(Display, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PriceApiVersion {
+    #[default]
+    V2,
+    V3,
+}
+
+impl PriceApiVersion {
+    pub fn base_url(&self) -> &'static str {
+        match self {
+            PriceApiVersion::V2 => JUP_API,
+            PriceApiVersion::V3 => JUP_API_V3,
+        }
+    }
 }
 
 const JUP_API: &str = "https://api.jup.ag/price/v2";
+const JUP_API_V3: &str = "https://api.jup.ag/price/v3";
+
+/// Maximum `ids` the price API accepts in one request.
+pub const DEFAULT_MAX_IDS_PER_REQUEST: usize = 100;
+pub const DEFAULT_MAX_CONCURRENT_REQUESTS: usize = 4;
+
+/// How `fetch_many_price_and_format` prices token pairs.
+#[derive(Display, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PairPricingMode {
+    /// One `vsToken` request per pair.
+    #[default]
+    VsToken,
+    /// Divide the legs' USD prices from the batched request, falling back to
+    /// `vsToken` for pairs with a leg that has no USD price.
+    DeriveFromUsd,
+}
+
+/// Result of `fetch_many_price_and_format`: formatted prices plus the error for
+/// every token or pair address that could not be priced.
+#[derive(Debug, Default)]
+pub struct PriceReport {
+    pub prices: HashMap<TokenOrPairAddress, TokenOrPairPriceInfo>,
+    pub errors: HashMap<TokenOrPairAddress, Arc<JupSdkError>>,
+}
+
+impl PriceReport {
+    pub fn is_complete(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// A batch request (or chunk of one) that failed.
+#[derive(Debug)]
+pub struct FailedChunk {
+    pub ids: Vec<String>,
+    pub error: JupSdkError,
+}
+
+/// Result of `fetch_many_prices`: prices found, ids the API had no price for,
+/// and chunks whose request failed.
+#[derive(Debug, Default)]
+pub struct PriceBatch {
+    pub prices: HashMap<String, f64>,
+    pub missing: Vec<String>,
+    pub failed: Vec<FailedChunk>,
+}
+
+impl PriceBatch {
+    /// Every requested id has a price.
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty() && self.failed.is_empty()
+    }
+
+    /// The prices, or the first chunk error if any chunk failed.
+    pub fn into_result(mut self) -> Result<HashMap<String, f64>> {
+        if self.failed.is_empty() {
+            Ok(self.prices)
+        } else {
+            Err(self.failed.swap_remove(0).error)
+        }
+    }
+}
 
 /// A dedicated struct for fetching prices.
 pub struct PriceFetcher {
     fetcher: Fetcher,
+    version: PriceApiVersion,
+    max_ids_per_request: usize,
+    max_concurrent_requests: usize,
+    pair_pricing_mode: PairPricingMode,
 }
 
 impl Default for PriceFetcher {
     fn default() -> Self {
-        Self {
-            fetcher: Fetcher::new(),
-        }
+        Self::new()
     }
 }
 
 impl PriceFetcher {
     /// Creates a new `PriceFetcher` with default settings.
     pub fn new() -> Self {
-        Self {
-            fetcher: Fetcher::new(),
-        }
+        Self::with_fetcher(Fetcher::new())
     }
 
     /// Creates a new `PriceFetcher` with custom settings.
     pub fn with_settings(settings: RetrySettings) -> Self {
+        Self::with_fetcher(Fetcher::with_settings(settings))
+    }
+
+    /// Creates a new `PriceFetcher` on top of an existing `Fetcher`.
+    /// Requests are tagged as `EndpointFamily::Price` for rate limiting.
+    pub fn with_fetcher(fetcher: Fetcher) -> Self {
         Self {
-            fetcher: Fetcher::with_settings(settings),
+            fetcher: fetcher.with_endpoint_family(EndpointFamily::Price),
+            version: PriceApiVersion::default(),
+            max_ids_per_request: DEFAULT_MAX_IDS_PER_REQUEST,
+            max_concurrent_requests: DEFAULT_MAX_CONCURRENT_REQUESTS,
+            pair_pricing_mode: PairPricingMode::default(),
         }
     }
 
+    pub fn with_pair_pricing_mode(mut self, mode: PairPricingMode) -> Self {
+        self.pair_pricing_mode = mode;
+        self
+    }
+
+    /// Caps how many ids `fetch_many_prices` puts in one request.
+    pub fn with_max_ids_per_request(mut self, max_ids: usize) -> Self {
+        self.max_ids_per_request = max_ids.max(1);
+        self
+    }
+
+    /// Caps how many chunk requests `fetch_many_prices` runs at once.
+    pub fn with_max_concurrent_requests(mut self, max_concurrent: usize) -> Self {
+        self.max_concurrent_requests = max_concurrent.max(1);
+        self
+    }
+
+    /// Selects the Price API version (v2 by default).
+    pub fn with_api_version(mut self, version: PriceApiVersion) -> Self {
+        self.version = version;
+        self
+    }
+
+    /// Fetches full quotes; tokens the API has no price for are left out.
+    pub async fn fetch_quotes(&self, addresses: &[&str]) -> Result<HashMap<String, PriceQuote>> {
+        let ids = addresses.join(",");
+        match self.version {
+            PriceApiVersion::V2 => {
+                let url = format!("{JUP_API}?ids={}&showExtraInfo=true", ids);
+                let response = self.fetcher.fetch_with_retry::<PriceResponse>(&url).await?;
+                response.into_quotes()
+            }
+            PriceApiVersion::V3 => {
+                let url = format!("{JUP_API_V3}?ids={}", ids);
+                let response = self
+                    .fetcher
+                    .fetch_with_retry::<PriceResponseV3>(&url)
+                    .await?;
+                Ok(response
+                    .into_iter()
+                    .filter_map(|(address, data)| {
+                        let data = data?;
+                        let quote = PriceQuote {
+                            id: address.clone(),
+                            price: data.usd_price,
+                            block_id: data.block_id,
+                            decimals: data.decimals,
+                            price_change_24h: data.price_change_24h,
+                            ..Default::default()
+                        };
+                        Some((address, quote))
+                    })
+                    .collect())
+            }
+        }
+    }
+
+    /// Fetches the full quote of a single token.
+    pub async fn fetch_quote(&self, address: &str) -> Result<PriceQuote> {
+        self.fetch_quotes(&[address]).await.and_then(|mut quotes| {
+            quotes
+                .remove(address)
+                .ok_or_else(|| JupSdkError::not_found(NotFoundKind::Token, address))
+        })
+    }
+
     /// Fetches the price of a single token.
     pub async fn fetch_price(&self, address: &str) -> Result<f64> {
-        let url = format!("{JUP_API}?ids={}", address);
-        self.fetch_price_internal(&url).await.and_then(|mut map| {
+        self.fetch_chunk(&[address]).await.and_then(|mut map| {
             map.remove(address)
-                .ok_or_else(|| anyhow!("Token {} not found", address))
+                .ok_or_else(|| JupSdkError::not_found(NotFoundKind::Token, address))
         })
     }
 
     /// Fetches the price of a token pair.
     pub async fn fetch_pair_price(&self, base: &str, vs: &str) -> Result<f64> {
+        if self.version == PriceApiVersion::V3 {
+            // v3 has no `vsToken`; derive the pair from both USD prices.
+            let mut prices = self.fetch_chunk(&[base, vs]).await?;
+            let base_price = prices
+                .remove(base)
+                .ok_or_else(|| JupSdkError::not_found(NotFoundKind::BaseToken, base))?;
+            let vs_price = prices
+                .remove(vs)
+                .ok_or_else(|| JupSdkError::not_found(NotFoundKind::Token, vs))?;
+            return Ok(base_price / vs_price);
+        }
+
         let url = format!("{JUP_API}?ids={}&vsToken={}", base, vs);
         self.fetch_price_internal(&url).await.and_then(|mut map| {
             map.remove(base)
-                .ok_or_else(|| anyhow!("Base token {} not found", base))
+                .ok_or_else(|| JupSdkError::not_found(NotFoundKind::BaseToken, base))
         })
     }
 
-    /// Fetches prices for multiple tokens.
-    pub async fn fetch_many_prices(&self, addresses: &[&str]) -> Result<HashMap<String, f64>> {
-        let params = addresses.join(",");
-        let url = format!("{JUP_API}?ids={}", params);
+    /// Fetches prices for multiple tokens, split into chunks of at most
+    /// `max_ids_per_request` ids that are fetched concurrently.
+    pub async fn fetch_many_prices(&self, addresses: &[&str]) -> PriceBatch {
+        let mut unique: Vec<&str> = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            if !unique.contains(address) {
+                unique.push(address);
+            }
+        }
+
+        let results: Vec<_> = stream::iter(unique.chunks(self.max_ids_per_request))
+            .map(|chunk| async move { (chunk, self.fetch_chunk(chunk).await) })
+            .buffer_unordered(self.max_concurrent_requests)
+            .collect()
+            .await;
+
+        let mut batch = PriceBatch::default();
+        for (chunk, result) in results {
+            match result {
+                Ok(prices) => {
+                    batch.missing.extend(
+                        chunk
+                            .iter()
+                            .filter(|id| !prices.contains_key(**id))
+                            .map(|id| id.to_string()),
+                    );
+                    batch.prices.extend(prices);
+                }
+                Err(error) => batch.failed.push(FailedChunk {
+                    ids: chunk.iter().map(|id| id.to_string()).collect(),
+                    error,
+                }),
+            }
+        }
+        batch
+    }
+
+    // One request for all `ids`.
+    async fn fetch_chunk(&self, ids: &[&str]) -> Result<HashMap<String, f64>> {
+        if self.version == PriceApiVersion::V3 {
+            let quotes = self.fetch_quotes(ids).await?;
+            return Ok(quotes
+                .into_iter()
+                .map(|(address, quote)| (address, quote.price))
+                .collect());
+        }
+
+        let url = format!("{JUP_API}?ids={}", ids.join(","));
         self.fetch_price_internal(&url).await
     }
 
@@ -146,63 +515,456 @@ impl PriceFetcher {
         }
     }
 
+    /// Fetches single-token prices (batched) and pair prices (concurrently).
+    /// Failures are reported per address instead of discarding the prices that succeeded.
+    ///
+    /// With `PairPricingMode::DeriveFromUsd`, pair legs are priced in the same batch as
+    /// the single tokens and only pairs with an unpriced leg fall back to `vsToken`.
     pub async fn fetch_many_price_and_format(
         &self,
         single_tokens: Vec<Token>,
         pairs: Vec<[Token; 2]>,
-    ) -> Option<HashMap<TokenOrPairAddress, TokenOrPairPriceInfo>> {
-        let mut all_prices: HashMap<TokenOrPairAddress, TokenOrPairPriceInfo> = HashMap::new();
-
-        // Fetch single token prices
-        if !single_tokens.is_empty() {
-            let single_addresses: Vec<&str> =
-                single_tokens.iter().map(|t| t.address.as_str()).collect();
-
-            if let Ok(prices) = self.fetch_many_prices(&single_addresses).await {
-                for token in single_tokens {
-                    if let Some(price) = prices.get(token.address.as_str()) {
-                        all_prices.insert(
-                            token.address.clone() as TokenOrPairAddress,
-                            TokenOrPairPriceInfo::Token(TokenPriceInfo {
-                                token: token.clone(),
-                                price_info: PriceInfo {
-                                    price: Some(*price),
-                                    ui_price: format_price(*price),
-                                    updated_at: get_unix_timestamp(),
-                                },
-                            }),
-                        );
-                    }
-                }
-            } else {
-                return None;
-            }
+    ) -> PriceReport {
+        let mut report = PriceReport::default();
+
+        let mut addresses: Vec<&str> = single_tokens.iter().map(|t| t.address.as_str()).collect();
+        if self.pair_pricing_mode == PairPricingMode::DeriveFromUsd {
+            addresses.extend(
+                pairs
+                    .iter()
+                    .flat_map(|[a, b]| [a.address.as_str(), b.address.as_str()]),
+            );
         }
 
-        // Fetch pair prices
-        for [token_a, token_b] in pairs {
-            // Directly iterate over the pairs
-            if let Ok(price) = self
-                .fetch_pair_price(&token_a.address, &token_b.address)
-                .await
-            {
-                all_prices.insert(
-                    format!("{}_{}", token_a.address, token_b.address) as TokenOrPairAddress,
-                    TokenOrPairPriceInfo::Pair(crate::feeder::PairPriceInfo {
-                        token_a: token_a.clone(),
-                        token_b: token_b.clone(),
+        // Fetch single token prices (and pair legs)
+        let batch = if addresses.is_empty() {
+            PriceBatch::default()
+        } else {
+            self.fetch_many_prices(&addresses).await
+        };
+
+        for token in &single_tokens {
+            if let Some(price) = batch.prices.get(token.address.as_str()) {
+                report.prices.insert(
+                    token.address.clone() as TokenOrPairAddress,
+                    TokenOrPairPriceInfo::Token(TokenPriceInfo {
+                        token: token.clone(),
                         price_info: PriceInfo {
-                            price: Some(price),
-                            ui_price: format_price(price),
+                            price: Some(*price),
+                            ui_price: format_price(*price),
                             updated_at: get_unix_timestamp(),
                         },
                     }),
                 );
-            } else {
-                return None;
+            } else if batch.missing.contains(&token.address) {
+                report.errors.insert(
+                    token.address.clone(),
+                    Arc::new(JupSdkError::not_found(NotFoundKind::Token, &token.address)),
+                );
+            }
+        }
+
+        let mut pair_results = Vec::with_capacity(pairs.len());
+        let mut vs_token_pairs = Vec::new();
+        let derive_pairs = self.pair_pricing_mode == PairPricingMode::DeriveFromUsd;
+        for [token_a, token_b] in pairs {
+            let derived = match (
+                batch.prices.get(token_a.address.as_str()),
+                batch.prices.get(token_b.address.as_str()),
+            ) {
+                (Some(base), Some(quote)) if derive_pairs && *quote > 0.0 => Some(base / quote),
+                _ => None,
+            };
+            match derived {
+                Some(price) => {
+                    pair_results.push((token_a, token_b, Ok((price, PairPriceMethod::Derived))))
+                }
+                None => vs_token_pairs.push([token_a, token_b]),
+            }
+        }
+
+        // Single tokens of a failed chunk share that chunk's error (pair legs fall back above).
+        for chunk in batch.failed {
+            let error = Arc::new(chunk.error);
+            for id in chunk.ids {
+                if single_tokens.iter().any(|t| t.address == id) {
+                    report.errors.insert(id, error.clone());
+                }
+            }
+        }
+
+        // Fetch the remaining pair prices
+        let fallback_method = match self.version {
+            PriceApiVersion::V2 => PairPriceMethod::VsToken,
+            PriceApiVersion::V3 => PairPriceMethod::Derived,
+        };
+        let fallback_results: Vec<_> = stream::iter(vs_token_pairs)
+            .map(|[token_a, token_b]| async move {
+                let price = self
+                    .fetch_pair_price(&token_a.address, &token_b.address)
+                    .await
+                    .map(|price| (price, fallback_method));
+                (token_a, token_b, price)
+            })
+            .buffer_unordered(self.max_concurrent_requests)
+            .collect()
+            .await;
+        pair_results.extend(fallback_results);
+
+        for (token_a, token_b, price) in pair_results {
+            let address = format!("{}_{}", token_a.address, token_b.address) as TokenOrPairAddress;
+            match price {
+                Ok((price, method)) => {
+                    report.prices.insert(
+                        address,
+                        TokenOrPairPriceInfo::Pair(PairPriceInfo {
+                            token_a,
+                            token_b,
+                            price_info: PriceInfo {
+                                price: Some(price),
+                                ui_price: format_price(price),
+                                updated_at: get_unix_timestamp(),
+                            },
+                            method,
+                        }),
+                    );
+                }
+                Err(error) => {
+                    report.errors.insert(address, Arc::new(error));
+                }
             }
         }
 
-        Some(all_prices)
+        report
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::token_registry::TokenSymbol;
+    use crate::transport::{MockResponse, MockTransport};
+    use serde_json::json;
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+    const UNKNOWN: &str = "UnknownMint1111111111111111111111111111111";
+
+    fn price_fetcher(mock: Arc<MockTransport>) -> PriceFetcher {
+        PriceFetcher::with_fetcher(Fetcher::new().with_transport(mock))
+    }
+
+    #[tokio::test]
+    async fn test_fetch_quotes_with_extra_info() {
+        let url = format!("{JUP_API}?ids={SOL},{UNKNOWN}&showExtraInfo=true");
+        let body = json!({
+            "data": {
+                SOL: {
+                    "id": SOL,
+                    "type": "derivedPrice",
+                    "price": "132.28",
+                    "extraInfo": {
+                        "lastSwappedPrice": {
+                            "lastJupiterSellAt": 1726231876,
+                            "lastJupiterSellPrice": "132.29",
+                            "lastJupiterBuyAt": 1726231877,
+                            "lastJupiterBuyPrice": "132.19"
+                        },
+                        "quotedPrice": {
+                            "buyPrice": "132.28",
+                            "buyAt": 1726231878,
+                            "sellPrice": "132.24",
+                            "sellAt": 1726231878
+                        },
+                        "confidenceLevel": "high",
+                        "depth": {
+                            "buyPriceImpactRatio": {
+                                "depth": { "10": 0.011, "100": 0.013, "1000": 0.027 },
+                                "timestamp": 1726231876
+                            },
+                            "sellPriceImpactRatio": {
+                                "depth": { "10": 0.015, "100": 0.016, "1000": 0.022 },
+                                "timestamp": 1726231876
+                            }
+                        }
+                    }
+                },
+                UNKNOWN: null
+            },
+            "timeTaken": 0.003
+        });
+        let mock = Arc::new(MockTransport::new().on(&url, MockResponse::json(200, &body)));
+
+        let quotes = price_fetcher(mock)
+            .fetch_quotes(&[SOL, UNKNOWN])
+            .await
+            .unwrap();
+
+        assert_eq!(quotes.len(), 1);
+        let sol = &quotes[SOL];
+        assert_eq!(sol.price, 132.28);
+        assert_eq!(sol.price_type.as_deref(), Some("derivedPrice"));
+        assert_eq!(sol.confidence_level, Some(ConfidenceLevel::High));
+        assert_eq!(
+            sol.last_swapped.as_ref().unwrap().last_jupiter_buy_price,
+            Some(132.19)
+        );
+        assert_eq!(sol.quoted.as_ref().unwrap().sell_price, Some(132.24));
+        let buy_depth = sol.depth.as_ref().unwrap().buy_price_impact_ratio.as_ref();
+        assert_eq!(buy_depth.unwrap().depth[&1000], 0.027);
+        assert!(sol.meets_confidence(ConfidenceLevel::Medium));
+    }
+
+    #[tokio::test]
+    async fn test_null_entries_are_skipped_for_prices() {
+        let url = format!("{JUP_API}?ids={UNKNOWN}");
+        let body = json!({ "data": { UNKNOWN: null }, "timeTaken": 0.001 });
+        let mock = Arc::new(MockTransport::new().on(&url, MockResponse::json(200, &body)));
+
+        let err = price_fetcher(mock).fetch_price(UNKNOWN).await.unwrap_err();
+        assert!(matches!(
+            err,
+            JupSdkError::NotFound {
+                kind: NotFoundKind::Token,
+                ..
+            }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_v3_quotes_and_derived_pair_price() {
+        let usdc = "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v";
+        let url = format!("{JUP_API_V3}?ids={SOL},{usdc}");
+        let body = json!({
+            SOL: { "usdPrice": 150.0, "blockId": 348004023, "decimals": 9, "priceChange24h": 1.29 },
+            usdc: { "usdPrice": 1.0, "blockId": 348004023, "decimals": 6, "priceChange24h": 0.0 }
+        });
+        let mock = Arc::new(MockTransport::new().on(&url, MockResponse::json(200, &body)));
+        let fetcher = price_fetcher(mock).with_api_version(PriceApiVersion::V3);
+
+        let quotes = fetcher.fetch_quotes(&[SOL, usdc]).await.unwrap();
+        assert_eq!(quotes[SOL].decimals, Some(9));
+        assert_eq!(quotes[SOL].price_change_24h, Some(1.29));
+        assert!(quotes[SOL].meets_confidence(ConfidenceLevel::High));
+
+        let pair = fetcher.fetch_pair_price(SOL, usdc).await.unwrap();
+        assert_eq!(pair, 150.0);
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_prices_in_chunks() {
+        let ids = ["A", "B", "C", "D", "E", "A"];
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    &format!("{JUP_API}?ids=A,B"),
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": { "A": { "id": "A", "type": "derivedPrice", "price": "1.5" }, "B": null },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                )
+                .on(
+                    &format!("{JUP_API}?ids=C,D"),
+                    MockResponse::text(400, "Too many ids"),
+                )
+                .on(
+                    &format!("{JUP_API}?ids=E"),
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": { "E": { "id": "E", "type": "derivedPrice", "price": "2" } },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                ),
+        );
+        let fetcher = price_fetcher(mock.clone())
+            .with_max_ids_per_request(2)
+            .with_max_concurrent_requests(2);
+
+        let batch = fetcher.fetch_many_prices(&ids).await;
+
+        assert_eq!(mock.requests().len(), 3);
+        assert_eq!(batch.prices.len(), 2);
+        assert_eq!(batch.prices["A"], 1.5);
+        assert_eq!(batch.prices["E"], 2.0);
+        assert_eq!(batch.missing, vec!["B".to_string()]);
+        assert_eq!(batch.failed.len(), 1);
+        assert_eq!(batch.failed[0].ids, vec!["C".to_string(), "D".to_string()]);
+        assert!(matches!(
+            batch.failed[0].error,
+            JupSdkError::HttpStatus { status: 400, .. }
+        ));
+        assert!(!batch.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_fetch_many_price_and_format_keeps_partial_results() {
+        let token = |symbol: &str| {
+            crate::token_registry::get_by_symbol(&symbol.parse::<TokenSymbol>().unwrap())
+                .unwrap()
+                .clone()
+        };
+        let (sol, jup, jlp, jupsol) = (token("SOL"), token("JUP"), token("JLP"), token("JupSOL"));
+        let price_body = |id: &str, price: &str| {
+            json!({
+                "data": { id: { "id": id, "type": "derivedPrice", "price": price } },
+                "timeTaken": 0.001
+            })
+        };
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    &format!("{JUP_API}?ids={},{}", sol.address, jup.address),
+                    MockResponse::json(200, &price_body(&sol.address, "150")),
+                )
+                .on(
+                    &format!("{JUP_API}?ids={}&vsToken={}", jupsol.address, sol.address),
+                    MockResponse::json(200, &price_body(&jupsol.address, "1.1")),
+                )
+                .on(
+                    &format!("{JUP_API}?ids={}&vsToken={}", jlp.address, sol.address),
+                    MockResponse::text(400, "bad pair"),
+                ),
+        );
+
+        let report = price_fetcher(mock)
+            .fetch_many_price_and_format(
+                vec![sol.clone(), jup.clone()],
+                vec![[jupsol.clone(), sol.clone()], [jlp.clone(), sol.clone()]],
+            )
+            .await;
+
+        assert_eq!(report.prices.len(), 2);
+        assert!(report.prices.contains_key(&sol.address));
+        assert!(report
+            .prices
+            .contains_key(&format!("{}_{}", jupsol.address, sol.address)));
+        assert_eq!(report.errors.len(), 2);
+        assert!(matches!(
+            report.errors[&jup.address].as_ref(),
+            JupSdkError::NotFound { .. }
+        ));
+        assert!(matches!(
+            report.errors[&format!("{}_{}", jlp.address, sol.address)].as_ref(),
+            JupSdkError::HttpStatus { status: 400, .. }
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_derive_pair_prices_from_usd_with_fallback() {
+        let token = |symbol: &str| {
+            crate::token_registry::get_by_symbol(&symbol.parse::<TokenSymbol>().unwrap())
+                .unwrap()
+                .clone()
+        };
+        let (sol, jlp, jupsol) = (token("SOL"), token("JLP"), token("JupSOL"));
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    &format!(
+                        "{JUP_API}?ids={},{},{}",
+                        sol.address, jupsol.address, jlp.address
+                    ),
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": {
+                                &sol.address: { "id": &sol.address, "type": "derivedPrice", "price": "150" },
+                                &jupsol.address: { "id": &jupsol.address, "type": "derivedPrice", "price": "165" },
+                                &jlp.address: null
+                            },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                )
+                .on(
+                    &format!("{JUP_API}?ids={}&vsToken={}", jlp.address, sol.address),
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": { &jlp.address: { "id": &jlp.address, "type": "derivedPrice", "price": "0.03" } },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                ),
+        );
+
+        let report = price_fetcher(mock.clone())
+            .with_pair_pricing_mode(PairPricingMode::DeriveFromUsd)
+            .fetch_many_price_and_format(
+                vec![sol.clone()],
+                vec![[jupsol.clone(), sol.clone()], [jlp.clone(), sol.clone()]],
+            )
+            .await;
+
+        assert!(report.is_complete());
+        assert_eq!(mock.requests().len(), 2);
+        let pair = |a: &Token| match &report.prices[&format!("{}_{}", a.address, sol.address)] {
+            TokenOrPairPriceInfo::Pair(info) => (info.price_info.price.unwrap(), info.method),
+            other => panic!("expected a pair, got {other:?}"),
+        };
+        assert_eq!(pair(&jupsol), (1.1, PairPriceMethod::Derived));
+        assert_eq!(pair(&jlp), (0.03, PairPriceMethod::VsToken));
+    }
+
+    #[tokio::test]
+    async fn test_vs_token_mode_never_derives_pairs() {
+        let token = |symbol: &str| {
+            crate::token_registry::get_by_symbol(&symbol.parse::<TokenSymbol>().unwrap())
+                .unwrap()
+                .clone()
+        };
+        let (sol, jupsol) = (token("SOL"), token("JupSOL"));
+        let pair_url = format!("{JUP_API}?ids={}&vsToken={}", jupsol.address, sol.address);
+
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(
+                    &format!("{JUP_API}?ids={},{}", sol.address, jupsol.address),
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": {
+                                &sol.address: { "id": &sol.address, "type": "derivedPrice", "price": "150" },
+                                &jupsol.address: { "id": &jupsol.address, "type": "derivedPrice", "price": "165" }
+                            },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                )
+                .on(
+                    &pair_url,
+                    MockResponse::json(
+                        200,
+                        &json!({
+                            "data": { &jupsol.address: { "id": &jupsol.address, "type": "derivedPrice", "price": "1.09" } },
+                            "timeTaken": 0.001
+                        }),
+                    ),
+                ),
+        );
+
+        // Both legs are priced in the batch, but the default mode still asks for vsToken.
+        let report = price_fetcher(mock.clone())
+            .fetch_many_price_and_format(
+                vec![sol.clone(), jupsol.clone()],
+                vec![[jupsol.clone(), sol.clone()]],
+            )
+            .await;
+
+        assert!(report.is_complete());
+        assert_eq!(mock.request_count(&pair_url), 1);
+        match &report.prices[&format!("{}_{}", jupsol.address, sol.address)] {
+            TokenOrPairPriceInfo::Pair(info) => {
+                assert_eq!(info.price_info.price, Some(1.09));
+                assert_eq!(info.method, PairPriceMethod::VsToken);
+            }
+            other => panic!("expected a pair, got {other:?}"),
+        }
     }
 }
@@ -0,0 +1,243 @@
+use crate::compat;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::Duration;
+use strum_macros::Display;
+
+/// Groups of endpoints that share a quota on Jupiter's side.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "snake_case")]
+pub enum EndpointFamily {
+    Price,
+    Perps,
+    Swap,
+}
+
+/// Token bucket parameters: up to `burst` requests at once, refilled at `refill_per_sec`.
+///
+/// A burst below one request could never be satisfied, so buckets use at least one.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Quota {
+    pub burst: f64,
+    pub refill_per_sec: f64,
+}
+
+impl Quota {
+    /// `requests` below one are treated as one.
+    pub fn per_second(requests: u32) -> Self {
+        let requests = requests.max(1) as f64;
+        Self {
+            burst: requests,
+            refill_per_sec: requests,
+        }
+    }
+
+    /// `requests` below one are treated as one.
+    pub fn per_minute(requests: u32) -> Self {
+        let requests = requests.max(1) as f64;
+        Self {
+            burst: requests,
+            refill_per_sec: requests / 60.0,
+        }
+    }
+
+    pub fn with_burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1) as f64;
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    quota: Quota,
+    tokens: f64,
+    last_refill_ms: f64,
+}
+
+impl Bucket {
+    fn new(mut quota: Quota, now_ms: f64) -> Self {
+        quota.burst = quota.burst.max(1.0);
+        Self {
+            quota,
+            tokens: quota.burst,
+            last_refill_ms: now_ms,
+        }
+    }
+
+    // Takes a token, or returns how long until one is available.
+    fn try_take(&mut self, now_ms: f64) -> Result<(), Duration> {
+        let elapsed_secs = (now_ms - self.last_refill_ms).max(0.0) / 1000.0;
+        self.tokens =
+            (self.tokens + elapsed_secs * self.quota.refill_per_sec).min(self.quota.burst);
+        self.last_refill_ms = now_ms;
+
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            Ok(())
+        } else if self.quota.refill_per_sec <= 0.0 {
+            // A bucket that never refills: keep callers polling slowly instead of spinning.
+            Err(Duration::from_secs(1))
+        } else {
+            Err(Duration::from_secs_f64(
+                (1.0 - self.tokens) / self.quota.refill_per_sec,
+            ))
+        }
+    }
+}
+
+/// Client-side token-bucket limiter, keyed by host.
+///
+/// Share one instance (`Arc<RateLimiter>`) between every `Fetcher` that talks to the
+/// same API key. Requests tagged with an `EndpointFamily` that has its own quota get a
+/// separate bucket per host and family; others use the host quota, then the default.
+#[derive(Debug, Default)]
+pub struct RateLimiter {
+    default_quota: Option<Quota>,
+    host_quotas: HashMap<String, Quota>,
+    family_quotas: HashMap<EndpointFamily, Quota>,
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// A limiter with no quotas (every request passes) until some are configured.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Quota for hosts without a more specific configuration.
+    pub fn with_default_quota(mut self, quota: Quota) -> Self {
+        self.default_quota = Some(quota);
+        self
+    }
+
+    pub fn with_host_quota(mut self, host: &str, quota: Quota) -> Self {
+        self.host_quotas.insert(host.to_string(), quota);
+        self
+    }
+
+    pub fn with_family_quota(mut self, family: EndpointFamily, quota: Quota) -> Self {
+        self.family_quotas.insert(family, quota);
+        self
+    }
+
+    fn bucket_for(&self, url: &str, family: Option<EndpointFamily>) -> Option<(String, Quota)> {
+        let host = host_of(url);
+        if let Some((family, quota)) =
+            family.and_then(|f| self.family_quotas.get(&f).map(|q| (f, *q)))
+        {
+            return Some((format!("{}#{}", host, family), quota));
+        }
+        self.host_quotas
+            .get(host)
+            .or(self.default_quota.as_ref())
+            .map(|quota| (host.to_string(), *quota))
+    }
+
+    /// Takes a token without waiting; on failure returns the time until one is available.
+    pub fn try_acquire(&self, url: &str, family: Option<EndpointFamily>) -> Result<(), Duration> {
+        let Some((key, quota)) = self.bucket_for(url, family) else {
+            return Ok(());
+        };
+        let now_ms = compat::now_millis();
+        let mut buckets = self.buckets.lock().unwrap();
+        buckets
+            .entry(key)
+            .or_insert_with(|| Bucket::new(quota, now_ms))
+            .try_take(now_ms)
+    }
+
+    /// Waits (via `compat::sleep`) until a request to `url` is allowed.
+    pub async fn acquire(&self, url: &str, family: Option<EndpointFamily>) {
+        while let Err(wait) = self.try_acquire(url, family) {
+            crate::platform_log!(warn, "Rate limited locally for {}, waiting {:?}", url, wait);
+            compat::sleep(wait).await;
+        }
+    }
+}
+
+// `https://api.jup.ag/price/v2?ids=..` -> `api.jup.ag`
+fn host_of(url: &str) -> &str {
+    let without_scheme = url.split_once("://").map_or(url, |(_, rest)| rest);
+    without_scheme
+        .split(['/', '?', '#'])
+        .next()
+        .unwrap_or(without_scheme)
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::time::Instant;
+
+    const PRICE_URL: &str = "https://api.jup.ag/price/v2?ids=SOL";
+
+    #[test]
+    fn test_host_of() {
+        assert_eq!(host_of(PRICE_URL), "api.jup.ag");
+        assert_eq!(
+            host_of("https://perps-api.jup.ag/v1/positions"),
+            "perps-api.jup.ag"
+        );
+        assert_eq!(host_of("localhost:8080"), "localhost:8080");
+    }
+
+    #[test]
+    fn test_buckets_by_host_and_family() {
+        let limiter = RateLimiter::new()
+            .with_host_quota("api.jup.ag", Quota::per_minute(1))
+            .with_family_quota(EndpointFamily::Swap, Quota::per_minute(2));
+
+        assert!(limiter.try_acquire(PRICE_URL, None).is_ok());
+        let wait = limiter.try_acquire(PRICE_URL, None).unwrap_err();
+        assert!(wait > Duration::from_secs(59) && wait <= Duration::from_secs(60));
+
+        // The swap family has its own bucket on the same host.
+        let swap_url = "https://api.jup.ag/swap/v1/quote";
+        assert!(limiter
+            .try_acquire(swap_url, Some(EndpointFamily::Swap))
+            .is_ok());
+        assert!(limiter
+            .try_acquire(swap_url, Some(EndpointFamily::Swap))
+            .is_ok());
+        assert!(limiter
+            .try_acquire(swap_url, Some(EndpointFamily::Swap))
+            .is_err());
+
+        // Hosts without a quota are not limited.
+        for _ in 0..10 {
+            assert!(limiter
+                .try_acquire("https://api-v3.raydium.io/pools", None)
+                .is_ok());
+        }
+    }
+
+    #[test]
+    fn test_quota_burst_is_at_least_one_request() {
+        assert_eq!(Quota::per_second(0), Quota::per_second(1));
+        assert_eq!(Quota::per_minute(0).burst, 1.0);
+        assert_eq!(Quota::per_second(10).with_burst(0).burst, 1.0);
+
+        let limiter = RateLimiter::new().with_default_quota(Quota {
+            burst: 0.5,
+            refill_per_sec: 1000.0,
+        });
+        assert!(limiter.try_acquire(PRICE_URL, None).is_ok());
+        let wait = limiter.try_acquire(PRICE_URL, None).unwrap_err();
+        assert!(wait <= Duration::from_millis(1));
+    }
+
+    #[tokio::test]
+    async fn test_acquire_waits_for_refill() {
+        let limiter =
+            Arc::new(RateLimiter::new().with_default_quota(Quota::per_second(20).with_burst(1)));
+        let started = Instant::now();
+        for _ in 0..3 {
+            limiter
+                .acquire(PRICE_URL, Some(EndpointFamily::Price))
+                .await;
+        }
+        // One token up front, then two refills at 50ms each.
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+}
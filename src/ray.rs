@@ -1,6 +1,10 @@
-use crate::fetcher::Fetcher;
+use crate::cache::{CachePolicy, ResponseCache};
+use crate::error::{JupSdkError, NotFoundKind, Result};
+use crate::fetcher::{Fetcher, RetrySettings};
+use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use strum_macros::{Display, EnumString}; // Import Fetcher
 
 #[derive(EnumString, Display, Debug, Clone)]
@@ -12,6 +16,12 @@ pub enum PoolId {
 
 pub const RAYDIUM_BASE_API: &str = "https://api-v3.raydium.io";
 
+// Response cache behind `RaydiumFetcher::shared` and the free functions built on it.
+// Only the cache is shared: HTTP clients stay per fetcher, so no connection pool is
+// tied to the runtime that happened to make the first call.
+static SHARED_CACHE: Lazy<Arc<ResponseCache>> =
+    Lazy::new(|| Arc::new(ResponseCache::in_memory(64, CachePolicy::default())));
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct PoolInfoResponse {
@@ -88,26 +98,60 @@ pub struct Settings {
     pub default_range_point: Vec<f64>,
 }
 
-// Use the Fetcher for consistency and retry logic
-async fn fetch_pool_info_internal(url: &str) -> anyhow::Result<PoolInfoResponse> {
-    // Create a default fetcher instance. Consider passing it if needed elsewhere.
-    let fetcher = Fetcher::default();
-    fetcher.fetch_with_retry::<PoolInfoResponse>(url).await
+/// A dedicated struct for the Raydium API.
+#[derive(Default)]
+pub struct RaydiumFetcher {
+    fetcher: Fetcher,
 }
 
-// Public function name remains the same
+impl RaydiumFetcher {
+    /// Creates a new `RaydiumFetcher` with default settings.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates a new `RaydiumFetcher` with custom settings.
+    pub fn with_settings(settings: RetrySettings) -> Self {
+        Self {
+            fetcher: Fetcher::with_settings(settings),
+        }
+    }
+
+    /// Creates a new `RaydiumFetcher` on top of an existing `Fetcher` (shared headers, transport, etc.).
+    pub fn with_fetcher(fetcher: Fetcher) -> Self {
+        Self { fetcher }
+    }
+
+    /// A fetcher with default settings that uses the process-wide Raydium response cache,
+    /// so repeated lookups within the cache TTL don't hit the API again.
+    pub fn shared() -> Self {
+        Self::shared_with(Fetcher::new())
+    }
+
+    fn shared_with(fetcher: Fetcher) -> Self {
+        Self::with_fetcher(fetcher.with_cache(SHARED_CACHE.clone()))
+    }
+
+    pub async fn fetch_pool_info_by_id(&self, id: PoolId) -> Result<PoolData> {
+        let url = format!("{RAYDIUM_BASE_API}/pools/info/ids?ids={id}");
+        let pool_info = self
+            .fetcher
+            .fetch_with_retry::<PoolInfoResponse>(&url)
+            .await?;
+
+        // Need to handle potential empty data list if ID not found
+        pool_info
+            .data
+            .into_iter()
+            .next()
+            .ok_or_else(|| JupSdkError::not_found(NotFoundKind::Pool, id.to_string()))
+    }
+}
+
+// Public function name remains the same; responses are served from the shared cache.
 #[allow(dead_code)]
-pub async fn fetch_pool_info_by_id(id: PoolId) -> anyhow::Result<PoolData> {
-    let url = format!("{RAYDIUM_BASE_API}/pools/info/ids?ids={id}");
-    let pool_info = fetch_pool_info_internal(&url).await?;
-
-    // Assuming the API always returns data if successful and the ID exists
-    // Need to handle potential empty data list if ID not found
-    pool_info
-        .data
-        .into_iter()
-        .next()
-        .ok_or_else(|| anyhow::anyhow!("Pool data not found for ID: {}", id))
+pub async fn fetch_pool_info_by_id(id: PoolId) -> Result<PoolData> {
+    RaydiumFetcher::shared().fetch_pool_info_by_id(id).await
 }
 
 #[allow(dead_code)]
@@ -119,7 +163,13 @@ pub fn get_token_logo_url_by_mint_address(mint_address: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{prices::MainTokenSymbol, ray, token_registry::TokenRegistry};
+    use crate::{
+        prices::MainTokenSymbol,
+        ray,
+        token_registry::TokenRegistry,
+        transport::{MockResponse, MockTransport},
+    };
+    use std::sync::Arc;
 
     // Requires tokio runtime, so only build/run with 'native' feature
     #[tokio::test]
@@ -153,4 +203,62 @@ mod tests {
             "https://img.raydium.io/icon/EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v.png"
         )
     }
+
+    #[tokio::test]
+    async fn test_fetch_pool_info_uses_shared_fetcher() {
+        let url = format!("{RAYDIUM_BASE_API}/pools/info/ids?ids={}", PoolId::SOL_JLP);
+        let mock = Arc::new(MockTransport::new().on(
+            &url,
+            MockResponse::json(
+                200,
+                &serde_json::json!({ "id": "mock", "success": true, "data": [] }),
+            ),
+        ));
+        let fetcher = Fetcher::new()
+            .with_transport(mock.clone())
+            .with_api_key("secret")
+            .with_default_header("x-client", "jup-sdk");
+
+        let result = RaydiumFetcher::with_fetcher(fetcher)
+            .fetch_pool_info_by_id(PoolId::SOL_JLP)
+            .await;
+
+        assert!(matches!(
+            result,
+            Err(JupSdkError::NotFound {
+                kind: NotFoundKind::Pool,
+                ..
+            })
+        ));
+        // Shared headers still apply, but the Jupiter API key stays on jup.ag hosts.
+        let request = mock.requests().pop().unwrap();
+        assert!(request
+            .headers
+            .contains(&("x-client".to_string(), "jup-sdk".to_string())));
+        assert!(!request
+            .headers
+            .iter()
+            .any(|(key, _)| key.eq_ignore_ascii_case("x-api-key")));
+    }
+
+    #[tokio::test]
+    async fn test_shared_fetchers_share_one_cache() {
+        let url = format!("{RAYDIUM_BASE_API}/pools/info/ids?ids={}", PoolId::SOL_JLP);
+        let mock = Arc::new(MockTransport::new().on(
+            &url,
+            MockResponse::json(
+                200,
+                &serde_json::json!({ "id": "mock", "success": true, "data": [] }),
+            ),
+        ));
+        let shared = || RaydiumFetcher::shared_with(Fetcher::new().with_transport(mock.clone()));
+
+        let _ = shared().fetch_pool_info_by_id(PoolId::SOL_JLP).await;
+        let requests = mock.request_count(&url);
+        let second = shared().fetch_pool_info_by_id(PoolId::SOL_JLP).await;
+
+        // The second fetcher is served from the cache the first one filled.
+        assert_eq!(mock.request_count(&url), requests);
+        assert!(matches!(second, Err(JupSdkError::NotFound { .. })));
+    }
 }
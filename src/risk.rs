@@ -0,0 +1,277 @@
+use crate::error::Result;
+use crate::perps::{PerpsFetcher, PerpsPosition, Side};
+use crate::prices::PriceFetcher;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Default `danger_band_percent`: flag positions within 10% of liquidation.
+pub const DEFAULT_DANGER_BAND_PERCENT: f64 = 10.0;
+
+/// How far a position is from liquidation at a given mark price.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct LiquidationRisk {
+    pub mark_price: f64,
+    pub liquidation_price: f64,
+    /// Adverse price move, in percent of the mark price, that triggers liquidation.
+    /// Zero or negative once the mark is past the liquidation price.
+    pub distance_percent: f64,
+    /// The same move in USD per unit of the market token.
+    pub distance_usd: f64,
+    /// 100 while the position is at least as far from liquidation as it was at entry,
+    /// falling linearly to 0 at the liquidation price.
+    pub health: f64,
+}
+
+impl PerpsPosition {
+    /// Liquidation risk at `mark_price`, or `None` when the position has no
+    /// liquidation price or the mark price isn't positive.
+    pub fn liquidation_risk(&self, mark_price: f64) -> Option<LiquidationRisk> {
+        if self.liquidation_price <= 0.0 || mark_price <= 0.0 {
+            return None;
+        }
+
+        // Signed distance to liquidation from `price`; positive while safe.
+        let distance_from = |price: f64| match self.side {
+            Side::Long => price - self.liquidation_price,
+            Side::Short => self.liquidation_price - price,
+        };
+        let distance_usd = distance_from(mark_price);
+        let distance_percent = distance_usd / mark_price * 100.0;
+
+        let entry_distance_percent = if self.entry_price > 0.0 {
+            distance_from(self.entry_price) / self.entry_price * 100.0
+        } else {
+            0.0
+        };
+        let health = if entry_distance_percent > 0.0 {
+            (distance_percent / entry_distance_percent).clamp(0.0, 1.0) * 100.0
+        } else if distance_percent > 0.0 {
+            100.0
+        } else {
+            0.0
+        };
+
+        Some(LiquidationRisk {
+            mark_price,
+            liquidation_price: self.liquidation_price,
+            distance_percent,
+            distance_usd,
+            health,
+        })
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionRisk {
+    pub position: PerpsPosition,
+    /// `None` when no mark price was available for the market.
+    pub risk: Option<LiquidationRisk>,
+    /// Within the danger band of liquidation (or past it).
+    pub in_danger: bool,
+}
+
+/// Liquidation risk across all positions of a wallet.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RiskReport {
+    pub danger_band_percent: f64,
+    /// Sorted by health, least healthy first; unpriced positions last.
+    pub positions: Vec<PositionRisk>,
+}
+
+impl RiskReport {
+    /// Builds a report from positions and mark prices keyed by market mint.
+    pub fn new(
+        positions: Vec<PerpsPosition>,
+        mark_prices: &HashMap<String, f64>,
+        danger_band_percent: f64,
+    ) -> Self {
+        let mut positions: Vec<PositionRisk> = positions
+            .into_iter()
+            .map(|position| {
+                let risk = mark_prices
+                    .get(&position.market_mint)
+                    .and_then(|mark| position.liquidation_risk(*mark));
+                let in_danger = risk.is_some_and(|r| r.distance_percent <= danger_band_percent);
+                PositionRisk {
+                    position,
+                    risk,
+                    in_danger,
+                }
+            })
+            .collect();
+        positions.sort_by(|a, b| {
+            let health = |p: &PositionRisk| p.risk.map_or(f64::INFINITY, |r| r.health);
+            health(a).total_cmp(&health(b))
+        });
+
+        Self {
+            danger_band_percent,
+            positions,
+        }
+    }
+
+    pub fn in_danger(&self) -> impl Iterator<Item = &PositionRisk> {
+        self.positions.iter().filter(|p| p.in_danger)
+    }
+
+    /// Positions whose market had no mark price.
+    pub fn unpriced(&self) -> impl Iterator<Item = &PositionRisk> {
+        self.positions.iter().filter(|p| p.risk.is_none())
+    }
+
+    /// Lowest health across priced positions.
+    pub fn min_health(&self) -> Option<f64> {
+        self.positions
+            .iter()
+            .filter_map(|p| p.risk.map(|r| r.health))
+            .min_by(f64::total_cmp)
+    }
+}
+
+/// Fetches a wallet's positions and their mark prices and builds a `RiskReport`.
+pub struct RiskMonitor {
+    prices: PriceFetcher,
+    perps: PerpsFetcher,
+    danger_band_percent: f64,
+}
+
+impl Default for RiskMonitor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RiskMonitor {
+    pub fn new() -> Self {
+        Self {
+            prices: PriceFetcher::new(),
+            perps: PerpsFetcher::new(),
+            danger_band_percent: DEFAULT_DANGER_BAND_PERCENT,
+        }
+    }
+
+    pub fn with_price_fetcher(mut self, prices: PriceFetcher) -> Self {
+        self.prices = prices;
+        self
+    }
+
+    pub fn with_perps_fetcher(mut self, perps: PerpsFetcher) -> Self {
+        self.perps = perps;
+        self
+    }
+
+    /// Flags positions whose distance to liquidation is at most this percent.
+    pub fn with_danger_band_percent(mut self, danger_band_percent: f64) -> Self {
+        self.danger_band_percent = danger_band_percent;
+        self
+    }
+
+    /// Fails if the positions or a price request fail, or a position has a malformed
+    /// amount; markets the price API has no price for are reported as unpriced.
+    pub async fn report(&self, wallet_address: &str) -> Result<RiskReport> {
+        let positions = self.perps.fetch_perps_positions(wallet_address).await?;
+        let mints: Vec<&str> = positions.iter().map(|p| p.market_mint.as_str()).collect();
+        let mark_prices = if mints.is_empty() {
+            HashMap::new()
+        } else {
+            self.prices.fetch_many_prices(&mints).await.into_result()?
+        };
+        Ok(RiskReport::new(
+            positions,
+            &mark_prices,
+            self.danger_band_percent,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perps::{PositionsResponse, POSITIONS_FIXTURE};
+
+    const SOL: &str = "So11111111111111111111111111111111111111112";
+
+    fn positions() -> Vec<PerpsPosition> {
+        serde_json::from_str::<PositionsResponse>(POSITIONS_FIXTURE)
+            .unwrap()
+            .data_list
+            .into_iter()
+            .map(|p| PerpsPosition::try_from(p).unwrap())
+            .collect()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-3,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_liquidation_risk_per_side() {
+        let [long, short]: [PerpsPosition; 2] = positions().try_into().unwrap();
+
+        // Long: entry 140, liquidation 126.434.
+        let risk = long.liquidation_risk(150.0).unwrap();
+        assert_close(risk.distance_usd, 23.566);
+        assert_close(risk.distance_percent, 23.566 / 150.0 * 100.0);
+        assert_eq!(risk.health, 100.0);
+
+        let risk = long.liquidation_risk(130.0).unwrap();
+        assert_close(risk.distance_percent, 3.566 / 130.0 * 100.0);
+        assert_close(risk.health, (3.566 / 130.0) / (13.566 / 140.0) * 100.0);
+        assert_eq!(long.liquidation_risk(120.0).unwrap().health, 0.0);
+
+        // Short: entry 160, liquidation 175.52.
+        let risk = short.liquidation_risk(170.0).unwrap();
+        assert_close(risk.distance_usd, 5.52);
+        assert_close(risk.distance_percent, 5.52 / 170.0 * 100.0);
+        assert_close(risk.health, (5.52 / 170.0) / (15.52 / 160.0) * 100.0);
+        assert!(short.liquidation_risk(180.0).unwrap().distance_percent < 0.0);
+
+        assert!(long.liquidation_risk(0.0).is_none());
+    }
+
+    #[test]
+    fn test_risk_report_flags_positions_in_danger_band() {
+        let marks = HashMap::from([(SOL.to_string(), 170.0)]);
+        let report = RiskReport::new(positions(), &marks, 5.0);
+
+        // At 170 the short is 3.2% from liquidation, the long 25.6%.
+        assert_eq!(report.positions[0].position.side, Side::Short);
+        let in_danger: Vec<_> = report.in_danger().collect();
+        assert_eq!(in_danger.len(), 1);
+        assert_eq!(in_danger[0].position.side, Side::Short);
+        assert_close(
+            report.min_health().unwrap(),
+            report.positions[0].risk.unwrap().health,
+        );
+
+        let report = RiskReport::new(positions(), &HashMap::new(), 5.0);
+        assert_eq!(report.unpriced().count(), 2);
+        assert_eq!(report.in_danger().count(), 0);
+        assert_eq!(report.min_health(), None);
+    }
+
+    #[cfg(all(feature = "log-native", feature = "native"))]
+    #[tokio::test]
+    async fn test_report_fails_on_malformed_liquidation_price() {
+        use crate::error::JupSdkError;
+        use crate::perps::{mock_positions_fetcher, MOCK_WALLET};
+
+        let mut fixture: serde_json::Value = serde_json::from_str(POSITIONS_FIXTURE).unwrap();
+        fixture["dataList"][1]["liquidationPrice"] = "NaN".into();
+        let (_, perps_fetcher) = mock_positions_fetcher(MOCK_WALLET, &fixture);
+        let monitor = RiskMonitor::new().with_perps_fetcher(perps_fetcher);
+
+        match monitor.report(MOCK_WALLET).await.unwrap_err() {
+            JupSdkError::Parse { field, id, .. } => {
+                assert_eq!(field, "liquidation_price");
+                assert_eq!(id, "3Hq8yPz6GdQ4u1NfWkLrT2sVbE9cXmA7oJiK5nD8eRtU");
+            }
+            other => panic!("Expected Parse error, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,495 @@
+//! What-if projections for Jupiter perps positions.
+//!
+//! Follows the perps program's rules: a 0.06% fee on the size opened or closed,
+//! PnL proportional to the price move from the (size-weighted) entry price, and
+//! liquidation once collateral minus PnL losses and pending close/borrow fees
+//! falls below `1 / MAX_LEVERAGE` of the size.
+//!
+//! Both constants mirror the perps program's on-chain parameters, as listed in
+//! the Jupiter Perpetuals docs ("Fees" and "Liquidation"): the custodies' base
+//! `increase_position_bps` / `decrease_position_bps` of 6 and `pricing.max_leverage`
+//! of 500x (positions open at up to 100x but are only liquidated past 500x).
+
+use crate::error::{parse_f64, Result};
+use crate::perps::{PerpsPosition, PositionData, Side};
+use serde::{Deserialize, Serialize};
+
+/// Fee charged on the USD size opened or closed (0.06%).
+pub const OPEN_CLOSE_FEE_RATE: f64 = 0.0006;
+/// Positions are liquidated past this leverage, i.e. a maintenance margin of 1/500.
+pub const MAX_LEVERAGE: f64 = 500.0;
+
+/// The inputs the simulator needs from a position. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionState {
+    pub side: Side,
+    pub entry_price: f64,
+    pub size: f64,
+    pub collateral: f64,
+    /// Borrow fees accrued so far, paid on close.
+    pub borrow_fees: f64,
+}
+
+impl TryFrom<&PositionData> for PositionState {
+    type Error = crate::error::JupSdkError;
+
+    fn try_from(position: &PositionData) -> Result<Self> {
+        let id = &position.position_pubkey;
+        Ok(Self {
+            side: position.side.clone(),
+            entry_price: parse_f64("entry_price", &position.entry_price, id)?,
+            size: parse_f64("size", &position.size, id)?,
+            collateral: parse_f64("collateral", &position.collateral, id)?,
+            borrow_fees: parse_f64("borrow_fees_usd", &position.borrow_fees_usd, id)?,
+        })
+    }
+}
+
+impl From<&PerpsPosition> for PositionState {
+    fn from(position: &PerpsPosition) -> Self {
+        Self {
+            side: position.side.clone(),
+            entry_price: position.entry_price,
+            size: position.size,
+            collateral: position.collateral,
+            borrow_fees: position.borrow_fees_usd,
+        }
+    }
+}
+
+/// A hypothetical change: a mark price plus optional collateral and size deltas (USD).
+///
+/// Deltas apply at `mark_price`: a positive `size_delta` increases the position,
+/// a negative one closes that much of it (the whole position at most).
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct Scenario {
+    pub mark_price: f64,
+    pub collateral_delta: f64,
+    pub size_delta: f64,
+}
+
+impl Scenario {
+    pub fn at(mark_price: f64) -> Self {
+        Self {
+            mark_price,
+            collateral_delta: 0.0,
+            size_delta: 0.0,
+        }
+    }
+
+    pub fn with_collateral_delta(mut self, collateral_delta: f64) -> Self {
+        self.collateral_delta = collateral_delta;
+        self
+    }
+
+    pub fn with_size_delta(mut self, size_delta: f64) -> Self {
+        self.size_delta = size_delta;
+        self
+    }
+}
+
+/// The projected position after a `Scenario`. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Simulation {
+    pub position: PositionState,
+    pub mark_price: f64,
+    pub pnl_before_fees_usd: f64,
+    /// PnL net of the close fee and accrued borrow fees, as the API reports it.
+    pub pnl_after_fees_usd: f64,
+    /// `pnl_after_fees_usd` in percent of collateral.
+    pub pnl_change_pct_after_fees: f64,
+    /// Collateral plus `pnl_after_fees_usd`.
+    pub value: f64,
+    pub leverage: f64,
+    /// `None` once the position is fully closed.
+    pub liquidation_price: Option<f64>,
+    /// Fee paid to open the added size.
+    pub open_fee_usd: f64,
+    /// Fee that closing the remaining position would cost.
+    pub close_fee_usd: f64,
+    /// PnL after fees paid out by a partial or full close.
+    pub realized_pnl_usd: f64,
+}
+
+impl Simulation {
+    /// The mark price is at or past the liquidation price.
+    pub fn is_liquidatable(&self) -> bool {
+        match (self.liquidation_price, &self.position.side) {
+            (Some(price), Side::Long) => self.mark_price <= price,
+            (Some(price), Side::Short) => self.mark_price >= price,
+            (None, _) => false,
+        }
+    }
+}
+
+impl PositionState {
+    /// PnL before fees if the price moves to `mark_price`.
+    pub fn pnl_before_fees(&self, mark_price: f64) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        let change = match self.side {
+            Side::Long => mark_price - self.entry_price,
+            Side::Short => self.entry_price - mark_price,
+        };
+        self.size * change / self.entry_price
+    }
+
+    /// Price at which the position gets liquidated, or `None` for an empty position.
+    pub fn liquidation_price(&self) -> Option<f64> {
+        if self.size <= 0.0 || self.entry_price <= 0.0 {
+            return None;
+        }
+        let margin = self.collateral
+            - self.size * OPEN_CLOSE_FEE_RATE
+            - self.borrow_fees
+            - self.size / MAX_LEVERAGE;
+        let move_to_liquidation = margin * self.entry_price / self.size;
+        Some(match self.side {
+            Side::Long => self.entry_price - move_to_liquidation,
+            Side::Short => self.entry_price + move_to_liquidation,
+        })
+    }
+
+    pub fn simulate(&self, scenario: &Scenario) -> Simulation {
+        let mark_price = scenario.mark_price;
+        let mut position = self.clone();
+        let mut open_fee_usd = 0.0;
+        let mut realized_pnl_usd = 0.0;
+
+        if scenario.size_delta > 0.0 {
+            // Increases pay the open fee from collateral and move the entry price to
+            // the size-weighted average (token amounts add up).
+            open_fee_usd = scenario.size_delta * OPEN_CLOSE_FEE_RATE;
+            position.collateral -= open_fee_usd;
+            if mark_price > 0.0 && position.entry_price > 0.0 {
+                position.entry_price = (position.size + scenario.size_delta)
+                    / (position.size / position.entry_price + scenario.size_delta / mark_price);
+            } else {
+                position.entry_price = mark_price;
+            }
+            position.size += scenario.size_delta;
+        } else if scenario.size_delta < 0.0 && position.size > 0.0 {
+            // Decreases settle the closed share of PnL, collateral and borrow fees.
+            let fraction = (-scenario.size_delta / position.size).min(1.0);
+            realized_pnl_usd = fraction
+                * (position.pnl_before_fees(mark_price)
+                    - position.size * OPEN_CLOSE_FEE_RATE
+                    - position.borrow_fees);
+            position.size *= 1.0 - fraction;
+            position.collateral *= 1.0 - fraction;
+            position.borrow_fees *= 1.0 - fraction;
+        }
+        position.collateral += scenario.collateral_delta;
+
+        let pnl_before_fees_usd = position.pnl_before_fees(mark_price);
+        let close_fee_usd = position.size * OPEN_CLOSE_FEE_RATE;
+        let pnl_after_fees_usd = if position.size > 0.0 {
+            pnl_before_fees_usd - close_fee_usd - position.borrow_fees
+        } else {
+            0.0
+        };
+        let ratio = |numerator: f64| {
+            if position.collateral > 0.0 {
+                numerator / position.collateral
+            } else {
+                0.0
+            }
+        };
+
+        Simulation {
+            mark_price,
+            pnl_before_fees_usd,
+            pnl_after_fees_usd,
+            pnl_change_pct_after_fees: ratio(pnl_after_fees_usd) * 100.0,
+            value: position.collateral + pnl_after_fees_usd,
+            leverage: ratio(position.size),
+            liquidation_price: position.liquidation_price(),
+            open_fee_usd,
+            close_fee_usd,
+            realized_pnl_usd,
+            position,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::perps::{PositionsResponse, POSITIONS_FIXTURE};
+
+    fn fixture() -> Vec<PositionData> {
+        serde_json::from_str::<PositionsResponse>(POSITIONS_FIXTURE)
+            .unwrap()
+            .data_list
+    }
+
+    fn assert_close(label: &str, actual: f64, expected: f64) {
+        assert_within(label, actual, expected, 1e-4);
+    }
+
+    fn assert_within(label: &str, actual: f64, expected: f64, tolerance: f64) {
+        assert!(
+            (actual - expected).abs() < tolerance,
+            "{}: {} != {}",
+            label,
+            actual,
+            expected
+        );
+    }
+
+    // Checks the simulator against positions as the API reports them at `mark_price`,
+    // within `tolerance` USD (the API rounds its amounts).
+    fn assert_reproduces_api_values(position: &PositionData, mark_price: f64, tolerance: f64) {
+        let simulation = PositionState::try_from(position)
+            .unwrap()
+            .simulate(&Scenario::at(mark_price));
+        let id = &position.position_pubkey;
+        let expect = |field: &str, value: &str| parse_f64(field, value, id).unwrap();
+
+        assert_within(
+            "pnl_before_fees",
+            simulation.pnl_before_fees_usd,
+            expect("pnl_before_fees_usd", &position.pnl_before_fees_usd),
+            tolerance,
+        );
+        assert_within(
+            "pnl_after_fees",
+            simulation.pnl_after_fees_usd,
+            expect("pnl_after_fees_usd", &position.pnl_after_fees_usd),
+            tolerance,
+        );
+        assert_within(
+            "value",
+            simulation.value,
+            expect("value", &position.value),
+            tolerance,
+        );
+        assert_within(
+            "leverage",
+            simulation.leverage,
+            expect("leverage", &position.leverage),
+            tolerance,
+        );
+        assert_within(
+            "liquidation_price",
+            simulation.liquidation_price.unwrap(),
+            expect("liquidation_price", &position.liquidation_price),
+            tolerance,
+        );
+        assert_within(
+            "close_fee",
+            simulation.close_fee_usd,
+            expect("close_fees_usd", &position.close_fees_usd),
+            tolerance,
+        );
+    }
+
+    #[test]
+    fn test_simulation_matches_fixture_values() {
+        // The fixture is synthetic (priced at a 150 USD mark) and its derived values
+        // were computed with these rules, so this only checks internal consistency.
+        for position in fixture() {
+            assert_reproduces_api_values(&position, 150.0, 1e-4);
+        }
+    }
+
+    // Raw `/positions?showTpslRequests=true` responses captured from the live API, one
+    // JSON file per capture. Record one with a wallet that has open positions:
+    // `RECORD_POSITIONS=1 WALLET_ADDRESS=... cargo test simulation_reproduces_live`.
+    const CAPTURED_POSITIONS_DIR: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures/positions");
+
+    fn captured_positions() -> Vec<(String, Vec<PositionData>)> {
+        let Ok(entries) = std::fs::read_dir(CAPTURED_POSITIONS_DIR) else {
+            return Vec::new();
+        };
+        let mut captures: Vec<_> = entries
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+            .map(|path| {
+                let raw = std::fs::read_to_string(&path).unwrap();
+                let response: PositionsResponse = serde_json::from_str(&raw).unwrap();
+                (path.display().to_string(), response.data_list)
+            })
+            .collect();
+        captures.sort_by(|a, b| a.0.cmp(&b.0));
+        captures
+    }
+
+    // The API doesn't report the mark price; recover it from the PnL before fees.
+    fn implied_mark_price(position: &PositionData) -> f64 {
+        let state = PositionState::try_from(position).unwrap();
+        let id = &position.position_pubkey;
+        let pnl = parse_f64("pnl_before_fees_usd", &position.pnl_before_fees_usd, id).unwrap();
+        let move_fraction = pnl / state.size;
+        match state.side {
+            Side::Long => state.entry_price * (1.0 + move_fraction),
+            Side::Short => state.entry_price * (1.0 - move_fraction),
+        }
+    }
+
+    // The API rounds its amounts, so captured values are compared to the cent.
+    fn assert_matches_captured(capture: &str, position: &PositionData) {
+        let simulation = PositionState::try_from(position)
+            .unwrap()
+            .simulate(&Scenario::at(implied_mark_price(position)));
+        let id = &position.position_pubkey;
+        let expect = |field: &str, value: &str| parse_f64(field, value, id).unwrap();
+        let label = |field: &str| format!("{} {} {}", capture, id, field);
+
+        assert_within(
+            &label("liquidationPrice"),
+            simulation.liquidation_price.unwrap(),
+            expect("liquidation_price", &position.liquidation_price),
+            0.01,
+        );
+        assert_within(
+            &label("pnlAfterFeesUsd"),
+            simulation.pnl_after_fees_usd,
+            expect("pnl_after_fees_usd", &position.pnl_after_fees_usd),
+            0.01,
+        );
+        assert_within(
+            &label("closeFeesUsd"),
+            simulation.close_fee_usd,
+            expect("close_fees_usd", &position.close_fees_usd),
+            0.01,
+        );
+    }
+
+    #[test]
+    fn test_simulation_matches_captured_positions() {
+        for (capture, positions) in captured_positions() {
+            for position in &positions {
+                assert_matches_captured(&capture, position);
+            }
+        }
+    }
+
+    // Runs against live positions of `WALLET_ADDRESS` (set in `.env`); with
+    // `RECORD_POSITIONS` set, also saves the raw response as a new capture.
+    #[cfg(all(feature = "log-native", feature = "native"))]
+    #[tokio::test]
+    async fn test_simulation_reproduces_live_api_values() {
+        dotenvy::from_filename(".env").ok();
+        let wallet_address =
+            std::env::var("WALLET_ADDRESS").expect("WALLET_ADDRESS not set in .env");
+        let raw: serde_json::Value = crate::fetcher::Fetcher::new()
+            .fetch_with_retry(&crate::perps::positions_url(&wallet_address))
+            .await
+            .unwrap();
+
+        if std::env::var_os("RECORD_POSITIONS").is_some() {
+            std::fs::create_dir_all(CAPTURED_POSITIONS_DIR).unwrap();
+            let path = format!(
+                "{}/{}.json",
+                CAPTURED_POSITIONS_DIR,
+                crate::time::get_unix_timestamp()
+            );
+            std::fs::write(&path, serde_json::to_string_pretty(&raw).unwrap()).unwrap();
+        }
+
+        let response: PositionsResponse = serde_json::from_value(raw).unwrap();
+        for position in &response.data_list {
+            assert_matches_captured("live", position);
+        }
+    }
+
+    #[test]
+    fn test_position_state_from_perps_position() {
+        for position in fixture() {
+            let from_data = PositionState::try_from(&position).unwrap();
+            let perps_position = PerpsPosition::try_from(position).unwrap();
+            assert_eq!(PositionState::from(&perps_position), from_data);
+        }
+    }
+
+    #[test]
+    fn test_what_if_scenarios() {
+        let [long, short]: [PositionState; 2] = fixture()
+            .iter()
+            .map(|p| PositionState::try_from(p).unwrap())
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap();
+
+        // (position, scenario, pnl before, pnl after, leverage, liquidation, realized)
+        let cases = [
+            (
+                &long,
+                Scenario::at(130.0),
+                -71.428571,
+                -72.528571,
+                10.0,
+                126.434,
+                0.0,
+            ),
+            (
+                &long,
+                Scenario::at(150.0).with_collateral_delta(50.0),
+                71.428571,
+                70.328571,
+                6.666667,
+                119.434,
+                0.0,
+            ),
+            (
+                &long,
+                Scenario::at(150.0).with_size_delta(500.0),
+                71.428571,
+                70.028571,
+                15.045135,
+                134.085,
+                0.0,
+            ),
+            (
+                &long,
+                Scenario::at(150.0).with_size_delta(-500.0),
+                35.714286,
+                35.164286,
+                10.0,
+                126.434,
+                35.164286,
+            ),
+            (
+                &short,
+                Scenario::at(170.0),
+                -31.25,
+                -31.75,
+                10.0,
+                175.52,
+                0.0,
+            ),
+            (
+                &short,
+                Scenario::at(150.0).with_collateral_delta(-20.0),
+                31.25,
+                30.75,
+                16.666667,
+                169.12,
+                0.0,
+            ),
+        ];
+
+        for (position, scenario, before, after, leverage, liquidation, realized) in cases {
+            let simulation = position.simulate(&scenario);
+            let label = format!("{:?} {:?}", position.side, scenario);
+            assert_close(&label, simulation.pnl_before_fees_usd, before);
+            assert_close(&label, simulation.pnl_after_fees_usd, after);
+            assert_close(&label, simulation.leverage, leverage);
+            assert_close(&label, simulation.liquidation_price.unwrap(), liquidation);
+            assert_close(&label, simulation.realized_pnl_usd, realized);
+        }
+
+        let increased = long.simulate(&Scenario::at(150.0).with_size_delta(500.0));
+        assert_close("entry", increased.position.entry_price, 143.181818);
+        assert_close("open_fee", increased.open_fee_usd, 0.3);
+
+        let closed = long.simulate(&Scenario::at(150.0).with_size_delta(-5000.0));
+        assert_eq!(closed.liquidation_price, None);
+        assert_close("realized", closed.realized_pnl_usd, 70.328571);
+
+        assert!(short.simulate(&Scenario::at(176.0)).is_liquidatable());
+        assert!(!short.simulate(&Scenario::at(175.0)).is_liquidatable());
+    }
+}
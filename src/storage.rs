@@ -0,0 +1,334 @@
+//! Persistence for collected prices, candles and perps position snapshots.
+//!
+//! Backends: `FileStorage` (`native`), `SqliteStorage` (`sqlite`), `KvStorage` (`worker`).
+//! Every record carries a schema version so data written by older releases can be
+//! upgraded on read (see `decode_record`).
+
+use crate::compat::{BoxFuture, MaybeSendSync};
+use crate::error::{JupSdkError, Result};
+use crate::feeder::PriceInfo;
+use crate::history::{Candle, Resolution};
+use crate::perps::PerpsPosition;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use strum_macros::Display;
+
+#[cfg(feature = "native")]
+mod file;
+#[cfg(feature = "worker")]
+mod kv;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "native")]
+pub use file::{FileFormat, FileStorage};
+#[cfg(feature = "worker")]
+pub use kv::KvStorage;
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteStorage;
+
+/// Version written with every record. Bump it together with a step in `upgrade`.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Positions of one wallet at one point in time (`timestamp` in unix seconds).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionSnapshot {
+    pub wallet: String,
+    pub timestamp: u64,
+    pub positions: Vec<PerpsPosition>,
+}
+
+/// Storage for price samples, candles and position snapshots.
+///
+/// Ranges are inclusive unix-second timestamps; results are sorted oldest first.
+/// Saving a record with an existing key (address + timestamp, or candle start)
+/// replaces it.
+pub trait Storage: MaybeSendSync {
+    fn save_prices<'a>(
+        &'a self,
+        address: &'a str,
+        prices: &'a [PriceInfo],
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn load_prices<'a>(
+        &'a self,
+        address: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PriceInfo>>>;
+
+    fn save_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        candles: &'a [Candle],
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn load_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<Candle>>>;
+
+    fn save_position_snapshot<'a>(
+        &'a self,
+        snapshot: &'a PositionSnapshot,
+    ) -> BoxFuture<'a, Result<()>>;
+
+    fn load_position_snapshots<'a>(
+        &'a self,
+        wallet: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PositionSnapshot>>>;
+}
+
+/// What a stored record holds; migrations are defined per kind.
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq)]
+#[strum(serialize_all = "snake_case")]
+pub enum RecordKind {
+    Price,
+    Candle,
+    PositionSnapshot,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Envelope<T> {
+    version: u32,
+    data: T,
+}
+
+/// Serializes `data` as `{"version": SCHEMA_VERSION, "data": ...}`.
+pub fn encode_record<T: Serialize>(data: &T) -> Result<String> {
+    serde_json::to_string(&Envelope {
+        version: SCHEMA_VERSION,
+        data,
+    })
+    .map_err(|e| JupSdkError::storage("encode", e))
+}
+
+/// Reads a record written by `encode_record` at any schema version (or a bare,
+/// unversioned record), upgrading it to the current schema first.
+pub fn decode_record<T: DeserializeOwned>(kind: RecordKind, raw: &str) -> Result<T> {
+    let value: serde_json::Value =
+        serde_json::from_str(raw).map_err(|e| JupSdkError::storage("decode", e))?;
+    let (version, data) = match value {
+        serde_json::Value::Object(mut object)
+            if object.contains_key("version") && object.contains_key("data") =>
+        {
+            let version = object
+                .get("version")
+                .and_then(|v| v.as_u64())
+                .unwrap_or_default() as u32;
+            (version, object.remove("data").unwrap_or_default())
+        }
+        // Written before records were versioned.
+        other => (0, other),
+    };
+    decode_value(kind, version, data)
+}
+
+/// Upgrades `data` stored at `version` and deserializes it.
+pub fn decode_value<T: DeserializeOwned>(
+    kind: RecordKind,
+    version: u32,
+    data: serde_json::Value,
+) -> Result<T> {
+    if version > SCHEMA_VERSION {
+        return Err(JupSdkError::storage(
+            "decode",
+            format!(
+                "{} record has schema version {}, newer than supported {}",
+                kind, version, SCHEMA_VERSION
+            ),
+        ));
+    }
+    let data = (version..SCHEMA_VERSION).fold(data, |data, from| upgrade(kind, from, data));
+    serde_json::from_value(data).map_err(|e| JupSdkError::storage("decode", e))
+}
+
+// Keeps the last record per key within `[from, to]`, ordered by key.
+#[cfg(any(feature = "native", feature = "worker"))]
+fn latest_in_range<T>(records: Vec<T>, from: u64, to: u64, key: impl Fn(&T) -> u64) -> Vec<T> {
+    records
+        .into_iter()
+        .filter(|r| (from..=to).contains(&key(r)))
+        .map(|r| (key(&r), r))
+        .collect::<std::collections::BTreeMap<_, _>>()
+        .into_values()
+        .collect()
+}
+
+// Shared by the backend tests: `0..=u64::MAX` loads everything that was saved.
+#[cfg(all(test, feature = "native"))]
+async fn assert_full_range_loads(storage: &impl Storage) {
+    let address = "So11111111111111111111111111111111111111112";
+    let prices = vec![PriceInfo {
+        price: Some(150.5),
+        ui_price: "150.50".to_string(),
+        updated_at: 1_735_689_600,
+    }];
+    let candle = Candle {
+        start: 1_735_689_600,
+        open: 1.0,
+        high: 2.0,
+        low: 0.5,
+        close: 1.5,
+        samples: 3,
+    };
+    let snapshot = PositionSnapshot {
+        wallet: "wallet".to_string(),
+        timestamp: 1_735_689_600,
+        positions: Vec::new(),
+    };
+    storage.save_prices(address, &prices).await.unwrap();
+    storage
+        .save_candles(address, Resolution::OneHour, &[candle])
+        .await
+        .unwrap();
+    storage.save_position_snapshot(&snapshot).await.unwrap();
+
+    assert_eq!(
+        storage.load_prices(address, 0, u64::MAX).await.unwrap(),
+        prices
+    );
+    assert_eq!(
+        storage
+            .load_candles(address, Resolution::OneHour, 0, u64::MAX)
+            .await
+            .unwrap(),
+        vec![candle]
+    );
+    assert_eq!(
+        storage
+            .load_position_snapshots("wallet", 0, u64::MAX)
+            .await
+            .unwrap(),
+        vec![snapshot]
+    );
+}
+
+// Migrates a record from `from` to `from + 1`.
+// v0 (unversioned) records already have the v1 shape.
+// v2 added the full `PerpsPosition` fields to position snapshots.
+fn upgrade(kind: RecordKind, from: u32, mut data: Value) -> Value {
+    if let (RecordKind::PositionSnapshot, 1) = (kind, from) {
+        let timestamp = data["timestamp"].clone();
+        if let Some(positions) = data.get_mut("positions").and_then(Value::as_array_mut) {
+            for position in positions.iter_mut().filter_map(Value::as_object_mut) {
+                upgrade_v1_position(position, &timestamp);
+            }
+        }
+    }
+    data
+}
+
+// v1 positions kept value, leverage and PnL after fees only. Collateral and size follow
+// from those (value = collateral + PnL, leverage = size / collateral); fees, the pubkey
+// and the open time weren't recorded, so they become zero, empty and the snapshot time.
+fn upgrade_v1_position(position: &mut Map<String, Value>, timestamp: &Value) {
+    let number = |key: &str| {
+        position
+            .get(key)
+            .and_then(Value::as_f64)
+            .unwrap_or_default()
+    };
+    let pnl = number("pnl_after_fees_usd");
+    let collateral = number("value") - pnl;
+    let size = collateral * number("leverage");
+    let entry_price = number("entry_price");
+    let pnl_pct = if collateral > 0.0 {
+        pnl / collateral * 100.0
+    } else {
+        0.0
+    };
+    let size_token_amount = if entry_price > 0.0 {
+        size / entry_price
+    } else {
+        0.0
+    };
+
+    let added = [
+        ("position_pubkey", json!("")),
+        ("size", json!(size)),
+        ("size_token_amount", json!(size_token_amount)),
+        ("collateral", json!(collateral)),
+        ("pnl_before_fees_usd", json!(pnl)),
+        ("pnl_change_pct_before_fees", json!(pnl_pct)),
+        ("pnl_change_pct_after_fees", json!(pnl_pct)),
+        ("borrow_fees_usd", json!(0.0)),
+        ("open_fees_usd", json!(0.0)),
+        ("close_fees_usd", json!(0.0)),
+        ("total_fees_usd", json!(0.0)),
+        ("take_profit_request", Value::Null),
+        ("stop_loss_request", Value::Null),
+        ("created_time", timestamp.clone()),
+        ("updated_time", timestamp.clone()),
+    ];
+    for (key, value) in added {
+        position.entry(key).or_insert(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_records_roundtrip_and_legacy_records_decode() {
+        let price = PriceInfo {
+            price: Some(150.5),
+            ui_price: "150.50".to_string(),
+            updated_at: 1_735_689_600,
+        };
+
+        let encoded = encode_record(&price).unwrap();
+        assert!(encoded.starts_with(r#"{"version":2,"#));
+        assert_eq!(
+            decode_record::<PriceInfo>(RecordKind::Price, &encoded).unwrap(),
+            price
+        );
+
+        let legacy = serde_json::to_string(&price).unwrap();
+        assert_eq!(
+            decode_record::<PriceInfo>(RecordKind::Price, &legacy).unwrap(),
+            price
+        );
+
+        // v1 position snapshots predate the full `PerpsPosition` fields.
+        let v1_data = r#"{"wallet":"wallet","timestamp":42,"positions":[{
+            "side":"long",
+            "market_mint":"So11111111111111111111111111111111111111112",
+            "collateral_mint":"So11111111111111111111111111111111111111112",
+            "entry_price":140.0,"leverage":10.0,"liquidation_price":126.434,
+            "pnl_after_fees_usd":70.328571,"value":170.328571,
+            "target_price":180.0,"stop_loss":130.0}]}"#;
+        let v1_snapshot = format!(r#"{{"version":1,"data":{}}}"#, v1_data);
+        let snapshot: PositionSnapshot =
+            decode_record(RecordKind::PositionSnapshot, &v1_snapshot).unwrap();
+        let position = &snapshot.positions[0];
+        assert_eq!(position.entry_price, 140.0);
+        assert!((position.collateral - 100.0).abs() < 1e-9);
+        assert!((position.size - 1000.0).abs() < 1e-9);
+        assert!((position.pnl_change_pct_after_fees - 70.328571).abs() < 1e-9);
+        assert_eq!(position.pnl_before_fees_usd, 70.328571);
+        assert_eq!((position.total_fees_usd, position.created_time), (0.0, 42));
+        assert_eq!(position.position_pubkey, "");
+        assert_eq!(position.stop_loss_request, None);
+        assert_eq!(position.stop_loss, Some(130.0));
+
+        // Unversioned snapshots take both steps.
+        assert_eq!(
+            decode_record::<PositionSnapshot>(RecordKind::PositionSnapshot, v1_data).unwrap(),
+            snapshot
+        );
+
+        let future = r#"{"version":99,"data":{}}"#;
+        assert!(matches!(
+            decode_record::<PriceInfo>(RecordKind::Price, future),
+            Err(JupSdkError::Storage { .. })
+        ));
+    }
+}
@@ -0,0 +1,510 @@
+use super::{
+    decode_record, decode_value, encode_record, latest_in_range, PositionSnapshot, RecordKind,
+    Storage, SCHEMA_VERSION,
+};
+use crate::compat::BoxFuture;
+use crate::error::{JupSdkError, Result};
+use crate::feeder::PriceInfo;
+use crate::history::{Candle, Resolution};
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+const PRICE_COLUMNS: &[&str] = &["version", "updated_at", "price", "ui_price"];
+const CANDLE_COLUMNS: &[&str] = &[
+    "version", "start", "open", "high", "low", "close", "samples",
+];
+
+/// On-disk layout of prices and candles. Position snapshots are always NDJSON.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FileFormat {
+    /// One versioned JSON record per line.
+    #[default]
+    Ndjson,
+    /// Header row followed by one row per record; the first column is the schema version.
+    Csv,
+}
+
+impl FileFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            FileFormat::Ndjson => "ndjson",
+            FileFormat::Csv => "csv",
+        }
+    }
+}
+
+/// Append-only file `Storage` rooted at a directory:
+/// `prices/{address}`, `candles/{address}_{resolution}` and `positions/{wallet}.ndjson`.
+///
+/// Loading reads the whole file; when a key was saved more than once the last write wins.
+pub struct FileStorage {
+    root: PathBuf,
+    format: FileFormat,
+    // Serializes appends so concurrent saves don't interleave lines.
+    write_lock: Mutex<()>,
+}
+
+impl FileStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            format: FileFormat::default(),
+            write_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn with_format(mut self, format: FileFormat) -> Self {
+        self.format = format;
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    fn prices_path(&self, operation: &str, address: &str) -> Result<PathBuf> {
+        Ok(self.root.join("prices").join(format!(
+            "{}.{}",
+            file_key(operation, address)?,
+            self.format.extension()
+        )))
+    }
+
+    fn candles_path(
+        &self,
+        operation: &str,
+        address: &str,
+        resolution: Resolution,
+    ) -> Result<PathBuf> {
+        Ok(self.root.join("candles").join(format!(
+            "{}_{}.{}",
+            file_key(operation, address)?,
+            resolution,
+            self.format.extension()
+        )))
+    }
+
+    fn positions_path(&self, operation: &str, wallet: &str) -> Result<PathBuf> {
+        Ok(self
+            .root
+            .join("positions")
+            .join(format!("{}.ndjson", file_key(operation, wallet)?)))
+    }
+
+    fn append(
+        &self,
+        operation: &str,
+        path: &Path,
+        header: &[&str],
+        lines: &[String],
+    ) -> Result<()> {
+        let _guard = self.write_lock.lock().unwrap();
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).map_err(|e| JupSdkError::storage(operation, e))?;
+        }
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .map_err(|e| JupSdkError::storage(operation, e))?;
+
+        let mut out = String::new();
+        if is_new && !header.is_empty() {
+            out.push_str(&header.join(","));
+            out.push('\n');
+        }
+        for line in lines {
+            out.push_str(line);
+            out.push('\n');
+        }
+        file.write_all(out.as_bytes())
+            .map_err(|e| JupSdkError::storage(operation, e))
+    }
+
+    fn save_records(
+        &self,
+        operation: &str,
+        path: &Path,
+        kind: RecordKind,
+        records: Vec<Value>,
+    ) -> Result<()> {
+        let (header, lines) = match self.format {
+            FileFormat::Ndjson => (
+                &[][..],
+                records
+                    .iter()
+                    .map(encode_record)
+                    .collect::<Result<Vec<_>>>()?,
+            ),
+            FileFormat::Csv => {
+                let columns = csv_columns(kind);
+                let lines = records
+                    .iter()
+                    .map(|record| {
+                        let mut fields = vec![SCHEMA_VERSION.to_string()];
+                        fields.extend(columns[1..].iter().map(|c| csv_field(&record[*c])));
+                        fields.join(",")
+                    })
+                    .collect();
+                (columns, lines)
+            }
+        };
+        self.append(operation, path, header, &lines)
+    }
+
+    fn load_records<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        path: &Path,
+        kind: RecordKind,
+        format: FileFormat,
+    ) -> Result<Vec<T>> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(JupSdkError::storage(operation, e)),
+        };
+
+        let mut lines = contents.lines().filter(|line| !line.trim().is_empty());
+        match format {
+            FileFormat::Ndjson => lines.map(|line| decode_record(kind, line)).collect(),
+            FileFormat::Csv => {
+                let header: Vec<String> = match lines.next() {
+                    Some(header) => parse_csv_line(header),
+                    None => return Ok(Vec::new()),
+                };
+                lines
+                    .map(|line| {
+                        let mut version = 0;
+                        let mut object = serde_json::Map::new();
+                        for (column, field) in header.iter().zip(parse_csv_line(line)) {
+                            if column == "version" {
+                                version = field.parse().map_err(|e| {
+                                    JupSdkError::storage(operation, format!("version: {}", e))
+                                })?;
+                            } else {
+                                object.insert(column.clone(), csv_value(column, field));
+                            }
+                        }
+                        decode_value(kind, version, Value::Object(object))
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+// Addresses and wallets become file names, so anything that could step out of the
+// storage root (separators, `..`, drive prefixes) is rejected.
+fn file_key<'a>(operation: &str, key: &'a str) -> Result<&'a str> {
+    if key.is_empty() || key.contains("..") || key.contains(['/', '\\', ':', '\0']) {
+        return Err(JupSdkError::storage(
+            operation,
+            format!("invalid key {:?} for a file name", key),
+        ));
+    }
+    Ok(key)
+}
+
+fn csv_columns(kind: RecordKind) -> &'static [&'static str] {
+    match kind {
+        RecordKind::Candle => CANDLE_COLUMNS,
+        _ => PRICE_COLUMNS,
+    }
+}
+
+fn csv_field(value: &Value) -> String {
+    let field = match value {
+        Value::Null => String::new(),
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    };
+    if field.contains([',', '"', '\n']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field
+    }
+}
+
+// Only `ui_price` is textual; every other column is a number, empty meaning null.
+fn csv_value(column: &str, field: String) -> Value {
+    if column == "ui_price" {
+        return Value::String(field);
+    }
+    if field.is_empty() {
+        return Value::Null;
+    }
+    serde_json::from_str(&field).unwrap_or(Value::String(field))
+}
+
+fn parse_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(std::mem::take(&mut field)),
+            c => field.push(c),
+        }
+    }
+    fields.push(field);
+    fields
+}
+
+fn to_values<T: serde::Serialize>(operation: &str, records: &[T]) -> Result<Vec<Value>> {
+    records
+        .iter()
+        .map(|r| serde_json::to_value(r).map_err(|e| JupSdkError::storage(operation, e)))
+        .collect()
+}
+
+impl Storage for FileStorage {
+    fn save_prices<'a>(
+        &'a self,
+        address: &'a str,
+        prices: &'a [PriceInfo],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let records = to_values("save prices", prices)?;
+            self.save_records(
+                "save prices",
+                &self.prices_path("save prices", address)?,
+                RecordKind::Price,
+                records,
+            )
+        })
+    }
+
+    fn load_prices<'a>(
+        &'a self,
+        address: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PriceInfo>>> {
+        Box::pin(async move {
+            let records = self.load_records(
+                "load prices",
+                &self.prices_path("load prices", address)?,
+                RecordKind::Price,
+                self.format,
+            )?;
+            Ok(latest_in_range(records, from, to, |p: &PriceInfo| {
+                p.updated_at
+            }))
+        })
+    }
+
+    fn save_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        candles: &'a [Candle],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let records = to_values("save candles", candles)?;
+            self.save_records(
+                "save candles",
+                &self.candles_path("save candles", address, resolution)?,
+                RecordKind::Candle,
+                records,
+            )
+        })
+    }
+
+    fn load_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<Candle>>> {
+        Box::pin(async move {
+            let records = self.load_records(
+                "load candles",
+                &self.candles_path("load candles", address, resolution)?,
+                RecordKind::Candle,
+                self.format,
+            )?;
+            Ok(latest_in_range(records, from, to, |c: &Candle| c.start))
+        })
+    }
+
+    fn save_position_snapshot<'a>(
+        &'a self,
+        snapshot: &'a PositionSnapshot,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let line = encode_record(snapshot)?;
+            self.append(
+                "save position snapshot",
+                &self.positions_path("save position snapshot", &snapshot.wallet)?,
+                &[],
+                &[line],
+            )
+        })
+    }
+
+    fn load_position_snapshots<'a>(
+        &'a self,
+        wallet: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PositionSnapshot>>> {
+        Box::pin(async move {
+            let records = self.load_records(
+                "load position snapshots",
+                &self.positions_path("load position snapshots", wallet)?,
+                RecordKind::PositionSnapshot,
+                FileFormat::Ndjson,
+            )?;
+            Ok(latest_in_range(
+                records,
+                from,
+                to,
+                |s: &PositionSnapshot| s.timestamp,
+            ))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::assert_full_range_loads;
+
+    fn temp_root(name: &str) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "jup-sdk-{}-{}-{}",
+            name,
+            std::process::id(),
+            crate::compat::now_millis()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        root
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_roundtrip_in_both_formats() {
+        let address = "So11111111111111111111111111111111111111112";
+        let prices = vec![
+            PriceInfo {
+                price: Some(100_000.5),
+                ui_price: "100,000".to_string(),
+                updated_at: 10,
+            },
+            PriceInfo {
+                price: None,
+                ui_price: "…".to_string(),
+                updated_at: 20,
+            },
+        ];
+        let candle = Candle {
+            start: 60,
+            open: 1.0,
+            high: 2.0,
+            low: 0.5,
+            close: 1.5,
+            samples: 3,
+        };
+
+        for format in [FileFormat::Ndjson, FileFormat::Csv] {
+            let root = temp_root(format.extension());
+            let storage = FileStorage::new(&root).with_format(format);
+
+            storage.save_prices(address, &prices).await.unwrap();
+            // Rewriting a key replaces it on load.
+            storage.save_prices(address, &prices[1..]).await.unwrap();
+            assert_eq!(storage.load_prices(address, 0, 100).await.unwrap(), prices);
+            assert_eq!(
+                storage.load_prices(address, 15, 100).await.unwrap(),
+                prices[1..]
+            );
+
+            storage
+                .save_candles(address, Resolution::OneMinute, &[candle])
+                .await
+                .unwrap();
+            assert_eq!(
+                storage
+                    .load_candles(address, Resolution::OneMinute, 0, 60)
+                    .await
+                    .unwrap(),
+                vec![candle]
+            );
+            assert!(storage
+                .load_candles(address, Resolution::OneHour, 0, 60)
+                .await
+                .unwrap()
+                .is_empty());
+
+            let snapshot = PositionSnapshot {
+                wallet: "wallet".to_string(),
+                timestamp: 42,
+                positions: Vec::new(),
+            };
+            storage.save_position_snapshot(&snapshot).await.unwrap();
+            assert_eq!(
+                storage
+                    .load_position_snapshots("wallet", 0, 100)
+                    .await
+                    .unwrap(),
+                vec![snapshot]
+            );
+
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_loads_full_range() {
+        for format in [FileFormat::Ndjson, FileFormat::Csv] {
+            let root = temp_root(&format!("full-range-{}", format.extension()));
+            assert_full_range_loads(&FileStorage::new(&root).with_format(format)).await;
+            fs::remove_dir_all(&root).unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn test_file_storage_rejects_keys_outside_root() {
+        let parent = temp_root("escape");
+        let storage = FileStorage::new(parent.join("root"));
+        let prices = [PriceInfo {
+            price: Some(1.0),
+            ui_price: "1.00".to_string(),
+            updated_at: 1,
+        }];
+
+        for key in ["../../x", "..", "a/b", "a\\b", "C:x", ""] {
+            assert!(matches!(
+                storage.save_prices(key, &prices).await,
+                Err(JupSdkError::Storage { .. })
+            ));
+            assert!(matches!(
+                storage.save_candles(key, Resolution::OneMinute, &[]).await,
+                Err(JupSdkError::Storage { .. })
+            ));
+            let snapshot = PositionSnapshot {
+                wallet: key.to_string(),
+                timestamp: 1,
+                positions: Vec::new(),
+            };
+            assert!(matches!(
+                storage.save_position_snapshot(&snapshot).await,
+                Err(JupSdkError::Storage { .. })
+            ));
+            assert!(storage.load_position_snapshots(key, 0, 1).await.is_err());
+        }
+        assert!(!parent.exists());
+    }
+}
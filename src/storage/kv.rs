@@ -0,0 +1,235 @@
+use super::{decode_record, encode_record, latest_in_range, PositionSnapshot, RecordKind, Storage};
+use crate::compat::BoxFuture;
+use crate::error::{JupSdkError, Result};
+use crate::feeder::PriceInfo;
+use crate::history::{Candle, Resolution};
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::BTreeMap;
+use worker::kv::KvStore;
+
+const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+/// Cloudflare Workers KV `Storage`.
+///
+/// Records are grouped into one value per UTC day, e.g. `prices:{address}:{day}`,
+/// holding newline-delimited versioned records. KV is eventually consistent and has
+/// no transactions, so concurrent saves to the same day may drop records.
+pub struct KvStorage {
+    store: KvStore,
+    prefix: String,
+}
+
+impl KvStorage {
+    pub fn new(store: KvStore) -> Self {
+        Self {
+            store,
+            prefix: String::new(),
+        }
+    }
+
+    /// Opens the KV namespace bound as `binding` in the worker environment.
+    pub fn from_env(env: &worker::Env, binding: &str) -> Result<Self> {
+        let store = env
+            .kv(binding)
+            .map_err(|e| JupSdkError::storage("open", e))?;
+        Ok(Self::new(store))
+    }
+
+    /// Prepends `prefix` to every key, to share a namespace with other data.
+    pub fn with_prefix(mut self, prefix: &str) -> Self {
+        self.prefix = prefix.to_string();
+        self
+    }
+
+    fn key_base(&self, kind: &str, id: &str) -> String {
+        format!("{}{}:{}:", self.prefix, kind, id)
+    }
+
+    async fn append<T: Serialize>(
+        &self,
+        operation: &str,
+        base: &str,
+        records: &[T],
+        timestamp: impl Fn(&T) -> u64,
+    ) -> Result<()> {
+        let mut by_day: BTreeMap<u64, Vec<String>> = BTreeMap::new();
+        for record in records {
+            by_day
+                .entry(timestamp(record) / SECS_PER_DAY)
+                .or_default()
+                .push(encode_record(record)?);
+        }
+
+        for (day, lines) in by_day {
+            // Zero-padded so keys list in day order.
+            let key = format!("{}{:06}", base, day);
+            let mut value = self
+                .store
+                .get(&key)
+                .text()
+                .await
+                .map_err(|e| JupSdkError::storage(operation, e))?
+                .unwrap_or_default();
+            for line in lines {
+                value.push_str(&line);
+                value.push('\n');
+            }
+            self.store
+                .put(&key, value)
+                .map_err(|e| JupSdkError::storage(operation, e))?
+                .execute()
+                .await
+                .map_err(|e| JupSdkError::storage(operation, e))?;
+        }
+        Ok(())
+    }
+
+    async fn load<T: DeserializeOwned>(
+        &self,
+        operation: &str,
+        base: &str,
+        kind: RecordKind,
+        from: u64,
+        to: u64,
+    ) -> Result<Vec<T>> {
+        let (first_day, last_day) = (from / SECS_PER_DAY, to / SECS_PER_DAY);
+        let mut records = Vec::new();
+        let mut cursor = None;
+        loop {
+            let mut list = self.store.list().prefix(base.to_string());
+            if let Some(cursor) = cursor.take() {
+                list = list.cursor(cursor);
+            }
+            let response = list
+                .execute()
+                .await
+                .map_err(|e| JupSdkError::storage(operation, e))?;
+
+            for key in response.keys {
+                let in_range = key.name[base.len()..]
+                    .parse::<u64>()
+                    .is_ok_and(|day| (first_day..=last_day).contains(&day));
+                if !in_range {
+                    continue;
+                }
+                let value = self
+                    .store
+                    .get(&key.name)
+                    .text()
+                    .await
+                    .map_err(|e| JupSdkError::storage(operation, e))?
+                    .unwrap_or_default();
+                for line in value.lines().filter(|line| !line.is_empty()) {
+                    records.push(decode_record(kind, line)?);
+                }
+            }
+
+            match response.cursor {
+                Some(next) if !response.list_complete => cursor = Some(next),
+                _ => return Ok(records),
+            }
+        }
+    }
+}
+
+impl Storage for KvStorage {
+    fn save_prices<'a>(
+        &'a self,
+        address: &'a str,
+        prices: &'a [PriceInfo],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let base = self.key_base("prices", address);
+            self.append("save prices", &base, prices, |p| p.updated_at)
+                .await
+        })
+    }
+
+    fn load_prices<'a>(
+        &'a self,
+        address: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PriceInfo>>> {
+        Box::pin(async move {
+            let base = self.key_base("prices", address);
+            let records = self
+                .load("load prices", &base, RecordKind::Price, from, to)
+                .await?;
+            Ok(latest_in_range(records, from, to, |p: &PriceInfo| {
+                p.updated_at
+            }))
+        })
+    }
+
+    fn save_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        candles: &'a [Candle],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let base = self.key_base("candles", &format!("{}:{}", address, resolution));
+            self.append("save candles", &base, candles, |c| c.start)
+                .await
+        })
+    }
+
+    fn load_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<Candle>>> {
+        Box::pin(async move {
+            let base = self.key_base("candles", &format!("{}:{}", address, resolution));
+            let records = self
+                .load("load candles", &base, RecordKind::Candle, from, to)
+                .await?;
+            Ok(latest_in_range(records, from, to, |c: &Candle| c.start))
+        })
+    }
+
+    fn save_position_snapshot<'a>(
+        &'a self,
+        snapshot: &'a PositionSnapshot,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let base = self.key_base("positions", &snapshot.wallet);
+            self.append(
+                "save position snapshot",
+                &base,
+                std::slice::from_ref(snapshot),
+                |s| s.timestamp,
+            )
+            .await
+        })
+    }
+
+    fn load_position_snapshots<'a>(
+        &'a self,
+        wallet: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PositionSnapshot>>> {
+        Box::pin(async move {
+            let base = self.key_base("positions", wallet);
+            let records = self
+                .load(
+                    "load position snapshots",
+                    &base,
+                    RecordKind::PositionSnapshot,
+                    from,
+                    to,
+                )
+                .await?;
+            Ok(latest_in_range(
+                records,
+                from,
+                to,
+                |s: &PositionSnapshot| s.timestamp,
+            ))
+        })
+    }
+}
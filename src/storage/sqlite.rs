@@ -0,0 +1,419 @@
+use super::{decode_value, PositionSnapshot, RecordKind, Storage, SCHEMA_VERSION};
+use crate::compat::BoxFuture;
+use crate::error::{JupSdkError, Result};
+use crate::feeder::PriceInfo;
+use crate::history::{Candle, Resolution};
+use rusqlite::{params, Connection};
+use std::path::Path;
+use std::sync::Mutex;
+
+// Applied in order; `PRAGMA user_version` records how many have run.
+const MIGRATIONS: &[&str] = &[
+    // 1: initial schema
+    "CREATE TABLE prices (
+        address TEXT NOT NULL,
+        updated_at INTEGER NOT NULL,
+        price REAL,
+        ui_price TEXT NOT NULL,
+        PRIMARY KEY (address, updated_at)
+    );
+    CREATE TABLE candles (
+        address TEXT NOT NULL,
+        resolution TEXT NOT NULL,
+        start INTEGER NOT NULL,
+        open REAL NOT NULL,
+        high REAL NOT NULL,
+        low REAL NOT NULL,
+        close REAL NOT NULL,
+        samples INTEGER NOT NULL,
+        PRIMARY KEY (address, resolution, start)
+    );
+    CREATE TABLE position_snapshots (
+        wallet TEXT NOT NULL,
+        timestamp INTEGER NOT NULL,
+        version INTEGER NOT NULL,
+        data TEXT NOT NULL,
+        PRIMARY KEY (wallet, timestamp)
+    );",
+];
+
+fn sql_error(operation: &str) -> impl Fn(rusqlite::Error) -> JupSdkError + '_ {
+    move |e| JupSdkError::storage(operation, e)
+}
+
+/// SQLite-backed `Storage`. Queries run synchronously on the calling task, which is
+/// fine for a local database file.
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+impl SqliteStorage {
+    /// Opens (or creates) the database at `path` and runs pending migrations.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Self::with_connection(Connection::open(path).map_err(sql_error("open"))?)
+    }
+
+    pub fn open_in_memory() -> Result<Self> {
+        Self::with_connection(Connection::open_in_memory().map_err(sql_error("open"))?)
+    }
+
+    fn with_connection(mut connection: Connection) -> Result<Self> {
+        migrate(&mut connection)?;
+        Ok(Self {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    /// Number of schema migrations applied to the database.
+    pub fn schema_version(&self) -> Result<usize> {
+        let connection = self.connection.lock().unwrap();
+        user_version(&connection)
+    }
+}
+
+fn user_version(connection: &Connection) -> Result<usize> {
+    connection
+        .query_row("PRAGMA user_version", [], |row| row.get::<_, i64>(0))
+        .map(|v| v as usize)
+        .map_err(sql_error("migrate"))
+}
+
+fn migrate(connection: &mut Connection) -> Result<()> {
+    let applied = user_version(connection)?;
+    if applied > MIGRATIONS.len() {
+        return Err(JupSdkError::storage(
+            "migrate",
+            format!(
+                "database schema {} is newer than supported {}",
+                applied,
+                MIGRATIONS.len()
+            ),
+        ));
+    }
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(applied) {
+        let tx = connection.transaction().map_err(sql_error("migrate"))?;
+        tx.execute_batch(migration).map_err(sql_error("migrate"))?;
+        tx.pragma_update(None, "user_version", (index + 1) as i64)
+            .map_err(sql_error("migrate"))?;
+        tx.commit().map_err(sql_error("migrate"))?;
+    }
+    Ok(())
+}
+
+// Timestamps go to and from SQLite as `u64` through rusqlite's checked conversion, so a
+// value past `i64::MAX` fails with a `Storage` error instead of wrapping. Range bounds
+// past it (e.g. `u64::MAX` for "everything") can't miss any stored row, so they're
+// clamped into range first.
+fn sql_bound(timestamp: u64) -> u64 {
+    timestamp.min(i64::MAX as u64)
+}
+
+impl Storage for SqliteStorage {
+    fn save_prices<'a>(
+        &'a self,
+        address: &'a str,
+        prices: &'a [PriceInfo],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut connection = self.connection.lock().unwrap();
+            let tx = connection.transaction().map_err(sql_error("save prices"))?;
+            {
+                let mut statement = tx
+                    .prepare_cached(
+                        "INSERT OR REPLACE INTO prices (address, updated_at, price, ui_price)
+                         VALUES (?1, ?2, ?3, ?4)",
+                    )
+                    .map_err(sql_error("save prices"))?;
+                for price in prices {
+                    statement
+                        .execute(params![
+                            address,
+                            price.updated_at,
+                            price.price,
+                            price.ui_price
+                        ])
+                        .map_err(sql_error("save prices"))?;
+                }
+            }
+            tx.commit().map_err(sql_error("save prices"))
+        })
+    }
+
+    fn load_prices<'a>(
+        &'a self,
+        address: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PriceInfo>>> {
+        Box::pin(async move {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare_cached(
+                    "SELECT price, ui_price, updated_at FROM prices
+                     WHERE address = ?1 AND updated_at BETWEEN ?2 AND ?3
+                     ORDER BY updated_at",
+                )
+                .map_err(sql_error("load prices"))?;
+            let rows = statement
+                .query_map(params![address, sql_bound(from), sql_bound(to)], |row| {
+                    Ok(PriceInfo {
+                        price: row.get(0)?,
+                        ui_price: row.get(1)?,
+                        updated_at: row.get(2)?,
+                    })
+                })
+                .map_err(sql_error("load prices"))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sql_error("load prices"))
+        })
+    }
+
+    fn save_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        candles: &'a [Candle],
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let mut connection = self.connection.lock().unwrap();
+            let tx = connection
+                .transaction()
+                .map_err(sql_error("save candles"))?;
+            {
+                let mut statement = tx
+                    .prepare_cached(
+                        "INSERT OR REPLACE INTO candles
+                         (address, resolution, start, open, high, low, close, samples)
+                         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                    )
+                    .map_err(sql_error("save candles"))?;
+                for candle in candles {
+                    statement
+                        .execute(params![
+                            address,
+                            resolution.to_string(),
+                            candle.start,
+                            candle.open,
+                            candle.high,
+                            candle.low,
+                            candle.close,
+                            candle.samples
+                        ])
+                        .map_err(sql_error("save candles"))?;
+                }
+            }
+            tx.commit().map_err(sql_error("save candles"))
+        })
+    }
+
+    fn load_candles<'a>(
+        &'a self,
+        address: &'a str,
+        resolution: Resolution,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<Candle>>> {
+        Box::pin(async move {
+            let connection = self.connection.lock().unwrap();
+            let mut statement = connection
+                .prepare_cached(
+                    "SELECT start, open, high, low, close, samples FROM candles
+                     WHERE address = ?1 AND resolution = ?2 AND start BETWEEN ?3 AND ?4
+                     ORDER BY start",
+                )
+                .map_err(sql_error("load candles"))?;
+            let rows = statement
+                .query_map(
+                    params![
+                        address,
+                        resolution.to_string(),
+                        sql_bound(from),
+                        sql_bound(to)
+                    ],
+                    |row| {
+                        Ok(Candle {
+                            start: row.get(0)?,
+                            open: row.get(1)?,
+                            high: row.get(2)?,
+                            low: row.get(3)?,
+                            close: row.get(4)?,
+                            samples: row.get(5)?,
+                        })
+                    },
+                )
+                .map_err(sql_error("load candles"))?;
+            rows.collect::<rusqlite::Result<Vec<_>>>()
+                .map_err(sql_error("load candles"))
+        })
+    }
+
+    fn save_position_snapshot<'a>(
+        &'a self,
+        snapshot: &'a PositionSnapshot,
+    ) -> BoxFuture<'a, Result<()>> {
+        Box::pin(async move {
+            let data = serde_json::to_string(snapshot)
+                .map_err(|e| JupSdkError::storage("save position snapshot", e))?;
+            let connection = self.connection.lock().unwrap();
+            connection
+                .execute(
+                    "INSERT OR REPLACE INTO position_snapshots (wallet, timestamp, version, data)
+                     VALUES (?1, ?2, ?3, ?4)",
+                    params![snapshot.wallet, snapshot.timestamp, SCHEMA_VERSION, data],
+                )
+                .map_err(sql_error("save position snapshot"))?;
+            Ok(())
+        })
+    }
+
+    fn load_position_snapshots<'a>(
+        &'a self,
+        wallet: &'a str,
+        from: u64,
+        to: u64,
+    ) -> BoxFuture<'a, Result<Vec<PositionSnapshot>>> {
+        Box::pin(async move {
+            let rows: Vec<(u32, String)> = {
+                let connection = self.connection.lock().unwrap();
+                let mut statement = connection
+                    .prepare_cached(
+                        "SELECT version, data FROM position_snapshots
+                         WHERE wallet = ?1 AND timestamp BETWEEN ?2 AND ?3
+                         ORDER BY timestamp",
+                    )
+                    .map_err(sql_error("load position snapshots"))?;
+                let rows = statement
+                    .query_map(params![wallet, sql_bound(from), sql_bound(to)], |row| {
+                        Ok((row.get(0)?, row.get(1)?))
+                    })
+                    .map_err(sql_error("load position snapshots"))?;
+                rows.collect::<rusqlite::Result<_>>()
+                    .map_err(sql_error("load position snapshots"))?
+            };
+
+            rows.into_iter()
+                .map(|(version, data)| {
+                    let value = serde_json::from_str(&data)
+                        .map_err(|e| JupSdkError::storage("load position snapshots", e))?;
+                    decode_value(RecordKind::PositionSnapshot, version, value)
+                })
+                .collect()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::storage::assert_full_range_loads;
+
+    #[tokio::test]
+    async fn test_sqlite_storage_migrates_and_roundtrips() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        assert_eq!(storage.schema_version().unwrap(), MIGRATIONS.len());
+
+        let address = "So11111111111111111111111111111111111111112";
+        let prices = vec![
+            PriceInfo {
+                price: Some(150.5),
+                ui_price: "150.50".to_string(),
+                updated_at: 10,
+            },
+            PriceInfo {
+                price: None,
+                ui_price: "…".to_string(),
+                updated_at: 20,
+            },
+        ];
+        storage.save_prices(address, &prices).await.unwrap();
+        storage.save_prices(address, &prices[1..]).await.unwrap();
+        assert_eq!(storage.load_prices(address, 0, 100).await.unwrap(), prices);
+        assert_eq!(
+            storage.load_prices(address, 11, 100).await.unwrap(),
+            prices[1..]
+        );
+
+        let candle = Candle {
+            start: 3600,
+            open: 1.0,
+            high: 2.0,
+            low: 0.5,
+            close: 1.5,
+            samples: 3,
+        };
+        storage
+            .save_candles(address, Resolution::OneHour, &[candle])
+            .await
+            .unwrap();
+        assert_eq!(
+            storage
+                .load_candles(address, Resolution::OneHour, 0, 3600)
+                .await
+                .unwrap(),
+            vec![candle]
+        );
+        assert!(storage
+            .load_candles(address, Resolution::OneMinute, 0, 3600)
+            .await
+            .unwrap()
+            .is_empty());
+
+        let snapshot = PositionSnapshot {
+            wallet: "wallet".to_string(),
+            timestamp: 42,
+            positions: Vec::new(),
+        };
+        storage.save_position_snapshot(&snapshot).await.unwrap();
+        assert_eq!(
+            storage
+                .load_position_snapshots("wallet", 0, 100)
+                .await
+                .unwrap(),
+            vec![snapshot]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_loads_full_range() {
+        assert_full_range_loads(&SqliteStorage::open_in_memory().unwrap()).await;
+    }
+
+    #[tokio::test]
+    async fn test_sqlite_storage_rejects_timestamps_past_i64() {
+        let storage = SqliteStorage::open_in_memory().unwrap();
+        let price = PriceInfo {
+            price: Some(1.0),
+            ui_price: "1.00".to_string(),
+            updated_at: u64::MAX,
+        };
+        let candle = Candle {
+            start: i64::MAX as u64 + 1,
+            open: 1.0,
+            high: 1.0,
+            low: 1.0,
+            close: 1.0,
+            samples: 1,
+        };
+        let snapshot = PositionSnapshot {
+            wallet: "wallet".to_string(),
+            timestamp: u64::MAX,
+            positions: Vec::new(),
+        };
+
+        let results = [
+            storage.save_prices("address", &[price]).await,
+            storage
+                .save_candles("address", Resolution::OneHour, &[candle])
+                .await,
+            storage.save_position_snapshot(&snapshot).await,
+        ];
+        for result in results {
+            assert!(matches!(result, Err(JupSdkError::Storage { .. })));
+        }
+        assert!(storage
+            .load_prices("address", 0, u64::MAX)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+}
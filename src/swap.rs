@@ -0,0 +1,499 @@
+use serde::{Deserialize, Serialize};
+use strum_macros::Display;
+
+use crate::{
+    error::{parse_f64, JupSdkError, Result},
+    fetcher::{Fetcher, RetrySettings},
+    rate_limit::EndpointFamily,
+    token_registry::Token,
+};
+
+pub const SWAP_API_BASE: &str = "https://quote-api.jup.ag/v6";
+
+const DEFAULT_SLIPPAGE_BPS: u16 = 50;
+
+/// Converts a UI amount (e.g. `1.5` SOL) to base units (lamports) using `decimals`.
+/// Fails on negative or non-finite amounts and on amounts that don't fit in a `u64`.
+pub fn to_base_units(ui_amount: f64, decimals: u8) -> Result<u64> {
+    let error = |reason: &str| JupSdkError::InvalidAmount {
+        amount: ui_amount,
+        decimals,
+        reason: reason.to_string(),
+    };
+    let base_units = (ui_amount * 10f64.powi(decimals as i32)).round();
+    if !base_units.is_finite() {
+        Err(error("not a finite number"))
+    } else if base_units < 0.0 {
+        Err(error("negative"))
+    } else if base_units >= u64::MAX as f64 {
+        // `u64::MAX as f64` rounds up to 2^64, the first value that doesn't fit.
+        Err(error("too large for u64 base units"))
+    } else {
+        Ok(base_units as u64)
+    }
+}
+
+/// Converts base units back to a UI amount using `decimals`.
+pub fn to_ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals as i32)
+}
+
+// The swap API encodes token amounts as decimal strings.
+mod u64_string {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &u64, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<u64, D::Error> {
+        let s: String = Deserialize::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+#[derive(Display, Default, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum SwapMode {
+    #[default]
+    ExactIn,
+    ExactOut,
+}
+
+/// Query parameters for `GET /quote`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuoteParams {
+    pub input_mint: String,
+    pub output_mint: String,
+    /// Amount in base units of the input mint (or output mint for `ExactOut`).
+    pub amount: u64,
+    pub slippage_bps: u16,
+    pub swap_mode: SwapMode,
+    pub only_direct_routes: bool,
+    pub restrict_intermediate_tokens: bool,
+    pub max_accounts: Option<u32>,
+}
+
+impl QuoteParams {
+    pub fn new(input_mint: &str, output_mint: &str, amount: u64) -> Self {
+        Self {
+            input_mint: input_mint.to_string(),
+            output_mint: output_mint.to_string(),
+            amount,
+            slippage_bps: DEFAULT_SLIPPAGE_BPS,
+            swap_mode: SwapMode::ExactIn,
+            only_direct_routes: false,
+            restrict_intermediate_tokens: false,
+            max_accounts: None,
+        }
+    }
+
+    /// Builds an `ExactIn` quote from a UI amount of `input`, using its `decimals`.
+    pub fn from_ui_amount(input: &Token, output: &Token, ui_amount: f64) -> Result<Self> {
+        Ok(Self::new(
+            &input.address,
+            &output.address,
+            to_base_units(ui_amount, input.decimals)?,
+        ))
+    }
+
+    pub fn with_slippage_bps(mut self, slippage_bps: u16) -> Self {
+        self.slippage_bps = slippage_bps;
+        self
+    }
+    pub fn with_swap_mode(mut self, swap_mode: SwapMode) -> Self {
+        self.swap_mode = swap_mode;
+        self
+    }
+    pub fn with_only_direct_routes(mut self, only_direct_routes: bool) -> Self {
+        self.only_direct_routes = only_direct_routes;
+        self
+    }
+    pub fn with_restrict_intermediate_tokens(mut self, restrict: bool) -> Self {
+        self.restrict_intermediate_tokens = restrict;
+        self
+    }
+    pub fn with_max_accounts(mut self, max_accounts: u32) -> Self {
+        self.max_accounts = Some(max_accounts);
+        self
+    }
+
+    fn to_query(&self) -> String {
+        let mut query = format!(
+            "inputMint={}&outputMint={}&amount={}&slippageBps={}&swapMode={}&onlyDirectRoutes={}&restrictIntermediateTokens={}",
+            self.input_mint,
+            self.output_mint,
+            self.amount,
+            self.slippage_bps,
+            self.swap_mode,
+            self.only_direct_routes,
+            self.restrict_intermediate_tokens
+        );
+        if let Some(max_accounts) = self.max_accounts {
+            query.push_str(&format!("&maxAccounts={}", max_accounts));
+        }
+        query
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformFee {
+    #[serde(with = "u64_string")]
+    pub amount: u64,
+    pub fee_bps: u16,
+    /// Fields not modeled here, kept so the quote round-trips unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapInfo {
+    pub amm_key: String,
+    #[serde(default)]
+    pub label: Option<String>,
+    pub input_mint: String,
+    pub output_mint: String,
+    #[serde(with = "u64_string")]
+    pub in_amount: u64,
+    #[serde(with = "u64_string")]
+    pub out_amount: u64,
+    #[serde(with = "u64_string")]
+    pub fee_amount: u64,
+    pub fee_mint: String,
+    /// Fields not modeled here, kept so the quote round-trips unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RoutePlan {
+    pub swap_info: SwapInfo,
+    pub percent: u8,
+    /// Fields not modeled here, kept so the quote round-trips unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+/// Response of `GET /quote`. Pass it back unchanged in a `SwapRequest`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct QuoteResponse {
+    pub input_mint: String,
+    #[serde(with = "u64_string")]
+    pub in_amount: u64,
+    pub output_mint: String,
+    #[serde(with = "u64_string")]
+    pub out_amount: u64,
+    #[serde(with = "u64_string")]
+    pub other_amount_threshold: u64,
+    pub swap_mode: SwapMode,
+    pub slippage_bps: u16,
+    #[serde(default)]
+    pub platform_fee: Option<PlatformFee>,
+    pub price_impact_pct: String,
+    pub route_plan: Vec<RoutePlan>,
+    #[serde(default)]
+    pub context_slot: Option<u64>,
+    #[serde(default)]
+    pub time_taken: Option<f64>,
+    /// Fields not modeled here, kept so the quote round-trips unchanged.
+    #[serde(flatten)]
+    pub extra: serde_json::Map<String, serde_json::Value>,
+}
+
+impl QuoteResponse {
+    /// The quoted output amount in UI units of `output`.
+    pub fn out_ui_amount(&self, output: &Token) -> f64 {
+        to_ui_amount(self.out_amount, output.decimals)
+    }
+
+    /// `price_impact_pct` as a number; fails instead of reading a malformed value as 0.
+    pub fn price_impact(&self) -> Result<f64> {
+        let id = format!("{}_{}", self.input_mint, self.output_mint);
+        parse_f64("price_impact_pct", &self.price_impact_pct, &id)
+    }
+}
+
+/// Body of `POST /swap` and `POST /swap-instructions`.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapRequest {
+    pub user_public_key: String,
+    pub quote_response: QuoteResponse,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wrap_and_unwrap_sol: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dynamic_compute_unit_limit: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prioritization_fee_lamports: Option<u64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub as_legacy_transaction: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub destination_token_account: Option<String>,
+}
+
+impl SwapRequest {
+    pub fn new(user_public_key: &str, quote_response: QuoteResponse) -> Self {
+        Self {
+            user_public_key: user_public_key.to_string(),
+            quote_response,
+            wrap_and_unwrap_sol: None,
+            dynamic_compute_unit_limit: None,
+            prioritization_fee_lamports: None,
+            as_legacy_transaction: None,
+            destination_token_account: None,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapResponse {
+    /// Base64-encoded serialized (unsigned) versioned transaction.
+    pub swap_transaction: String,
+    pub last_valid_block_height: u64,
+    #[serde(default)]
+    pub prioritization_fee_lamports: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AccountMeta {
+    pub pubkey: String,
+    pub is_signer: bool,
+    pub is_writable: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Instruction {
+    pub program_id: String,
+    pub accounts: Vec<AccountMeta>,
+    /// Base64-encoded instruction data.
+    pub data: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SwapInstructionsResponse {
+    #[serde(default)]
+    pub token_ledger_instruction: Option<Instruction>,
+    #[serde(default)]
+    pub compute_budget_instructions: Vec<Instruction>,
+    #[serde(default)]
+    pub setup_instructions: Vec<Instruction>,
+    pub swap_instruction: Instruction,
+    #[serde(default)]
+    pub cleanup_instruction: Option<Instruction>,
+    #[serde(default)]
+    pub address_lookup_table_addresses: Vec<String>,
+}
+
+/// A dedicated struct for the Jupiter Swap API (v6).
+pub struct SwapFetcher {
+    fetcher: Fetcher,
+}
+
+impl Default for SwapFetcher {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl SwapFetcher {
+    /// Creates a new `SwapFetcher` with default settings.
+    pub fn new() -> Self {
+        Self::with_fetcher(Fetcher::new())
+    }
+
+    /// Creates a new `SwapFetcher` with custom settings.
+    pub fn with_settings(settings: RetrySettings) -> Self {
+        Self::with_fetcher(Fetcher::with_settings(settings))
+    }
+
+    /// Creates a new `SwapFetcher` on top of an existing `Fetcher`.
+    /// Requests are tagged as `EndpointFamily::Swap` for rate limiting.
+    pub fn with_fetcher(fetcher: Fetcher) -> Self {
+        Self {
+            fetcher: fetcher.with_endpoint_family(EndpointFamily::Swap),
+        }
+    }
+
+    /// Fetches the best route for `params`.
+    pub async fn fetch_quote(&self, params: &QuoteParams) -> Result<QuoteResponse> {
+        let url = format!("{SWAP_API_BASE}/quote?{}", params.to_query());
+        self.fetcher.fetch_with_retry::<QuoteResponse>(&url).await
+    }
+
+    /// Builds a serialized swap transaction for a previously fetched quote.
+    pub async fn fetch_swap_transaction(&self, request: &SwapRequest) -> Result<SwapResponse> {
+        let url = format!("{SWAP_API_BASE}/swap");
+        self.fetcher.post_with_retry(&url, request).await
+    }
+
+    /// Returns the individual instructions instead of a full transaction.
+    pub async fn fetch_swap_instructions(
+        &self,
+        request: &SwapRequest,
+    ) -> Result<SwapInstructionsResponse> {
+        let url = format!("{SWAP_API_BASE}/swap-instructions");
+        self.fetcher.post_with_retry(&url, request).await
+    }
+}
+
+#[cfg(all(test, feature = "native"))]
+mod tests {
+    use super::*;
+    use crate::{
+        prices::MainTokenSymbol,
+        token_registry::TokenRegistry,
+        transport::{Method, MockResponse, MockTransport},
+    };
+    use std::sync::Arc;
+
+    const QUOTE_FIXTURE: &str = r#"{
+      "inputMint": "So11111111111111111111111111111111111111112",
+      "inAmount": "1500000000",
+      "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+      "outAmount": "225123456",
+      "otherAmountThreshold": "224997890",
+      "swapMode": "ExactIn",
+      "slippageBps": 50,
+      "platformFee": null,
+      "priceImpactPct": "0.0001",
+      "routePlan": [
+        {
+          "swapInfo": {
+            "ammKey": "HcoJqG325TTifs6jyWvRJ9ET4pDu12Xrt2EQKZGFmuKX",
+            "label": "Whirlpool",
+            "inputMint": "So11111111111111111111111111111111111111112",
+            "outputMint": "EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v",
+            "inAmount": "1500000000",
+            "outAmount": "225123456",
+            "feeAmount": "45000",
+            "feeMint": "So11111111111111111111111111111111111111112",
+            "ammVersion": 2
+          },
+          "percent": 100,
+          "bps": 10000
+        }
+      ],
+      "contextSlot": 299283763,
+      "timeTaken": 0.0125,
+      "swapUsdValue": "225.12",
+      "otherRoutePlans": [],
+      "mostReliableAmmsQuoteReport": { "info": {} }
+    }"#;
+
+    #[test]
+    fn test_quote_params_from_ui_amount() {
+        let registry = TokenRegistry::new();
+        let sol = registry.get_by_symbol(&MainTokenSymbol::SOL).unwrap();
+        let usdc = registry.get_by_symbol(&MainTokenSymbol::USDC).unwrap();
+
+        let params = QuoteParams::from_ui_amount(sol, usdc, 1.5)
+            .unwrap()
+            .with_slippage_bps(100)
+            .with_only_direct_routes(true)
+            .with_max_accounts(64);
+
+        assert_eq!(params.amount, 1_500_000_000);
+        assert_eq!(
+            params.to_query(),
+            "inputMint=So11111111111111111111111111111111111111112&outputMint=EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v&amount=1500000000&slippageBps=100&swapMode=ExactIn&onlyDirectRoutes=true&restrictIntermediateTokens=false&maxAccounts=64"
+        );
+        assert!(matches!(
+            QuoteParams::from_ui_amount(sol, usdc, -1.0),
+            Err(JupSdkError::InvalidAmount { .. })
+        ));
+    }
+
+    #[test]
+    fn test_to_base_units_rejects_unrepresentable_amounts() {
+        assert_eq!(to_base_units(1.5, 9).unwrap(), 1_500_000_000);
+        assert_eq!(to_base_units(0.0, 6).unwrap(), 0);
+        assert_eq!(to_base_units(-0.0, 6).unwrap(), 0);
+        assert_eq!(to_base_units(1e19, 0).unwrap(), 10_000_000_000_000_000_000);
+
+        for (amount, decimals) in [
+            (-1.0, 9),
+            (-1e-6, 9),
+            (f64::NAN, 9),
+            (f64::INFINITY, 9),
+            (f64::NEG_INFINITY, 9),
+            (18_446_744_073.709_553, 9),
+            (1e300, 9),
+            (1.0, 255),
+        ] {
+            match to_base_units(amount, decimals) {
+                Err(JupSdkError::InvalidAmount {
+                    decimals: reported, ..
+                }) => assert_eq!(reported, decimals),
+                other => panic!("{} with {} decimals: got {:?}", amount, decimals, other),
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fetch_quote_and_swap_with_mock() -> Result<()> {
+        let usdc =
+            crate::token_registry::get_by_address("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v")
+                .unwrap();
+        let params = QuoteParams::new(
+            "So11111111111111111111111111111111111111112",
+            &usdc.address,
+            1_500_000_000,
+        );
+        let quote_url = format!("{SWAP_API_BASE}/quote?{}", params.to_query());
+        let swap_url = format!("{SWAP_API_BASE}/swap");
+        let quote_json: serde_json::Value = serde_json::from_str(QUOTE_FIXTURE).unwrap();
+        let mock = Arc::new(
+            MockTransport::new()
+                .on(&quote_url, MockResponse::json(200, &quote_json))
+                .on(
+                    &swap_url,
+                    MockResponse::json(
+                        200,
+                        &serde_json::json!({
+                            "swapTransaction": "AQAAAA==",
+                            "lastValidBlockHeight": 279632475,
+                            "prioritizationFeeLamports": 9999
+                        }),
+                    ),
+                ),
+        );
+        let swap_fetcher = SwapFetcher::with_fetcher(Fetcher::new().with_transport(mock.clone()));
+
+        let quote = swap_fetcher.fetch_quote(&params).await?;
+        assert_eq!(quote.out_amount, 225_123_456);
+        assert_eq!(
+            quote.route_plan[0].swap_info.label.as_deref(),
+            Some("Whirlpool")
+        );
+        assert!((quote.out_ui_amount(usdc) - 225.123456).abs() < 1e-9);
+        assert_eq!(quote.price_impact()?, 0.0001);
+        let mut malformed = quote.clone();
+        malformed.price_impact_pct = "n/a".to_string();
+        assert!(matches!(
+            malformed.price_impact(),
+            Err(crate::error::JupSdkError::Parse { field, .. }) if field == "price_impact_pct"
+        ));
+
+        let request = SwapRequest::new("MockUser1111111111111111111111111111111111", quote);
+        let swap = swap_fetcher.fetch_swap_transaction(&request).await?;
+        assert_eq!(swap.swap_transaction, "AQAAAA==");
+
+        // The quote must be sent back exactly as received.
+        let sent = mock.requests().pop().unwrap();
+        assert_eq!(sent.method, Method::Post);
+        let body: serde_json::Value = serde_json::from_slice(&sent.body.unwrap()).unwrap();
+        assert_eq!(body["quoteResponse"], quote_json);
+        assert_eq!(
+            body["userPublicKey"],
+            "MockUser1111111111111111111111111111111111"
+        );
+        Ok(())
+    }
+}
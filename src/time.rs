@@ -6,3 +6,23 @@ pub fn get_unix_timestamp() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+/// UTC calendar date `(year, month, day)` of a unix timestamp in seconds.
+pub fn utc_date(timestamp: i64) -> (i32, u32, u32) {
+    // Days-to-civil conversion from Howard Hinnant's date algorithms.
+    let days = timestamp.div_euclid(86_400) + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = (year_of_era + era * 400 + i64::from(month <= 2)) as i32;
+    (year, month, day)
+}
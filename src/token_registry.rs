@@ -3,6 +3,7 @@ use serde::{Deserialize, Serialize};
 use serde_json;
 use std::{collections::HashMap, fmt, str::FromStr};
 
+use crate::error::{JupSdkError, NotFoundKind};
 use crate::prices::MainTokenSymbol;
 
 // Embedded JSON data
@@ -42,6 +43,27 @@ const TOKENS_JSON: &str = r#"
     "name": "USD Coin",
     "decimals": 6,
     "stable": true
+  },
+  {
+    "address": "Es9vMFrzaCERmJfrF4H2FYD4KCoNkY11McCe8BenwNYB",
+    "symbol": "USDT",
+    "name": "USDT",
+    "decimals": 6,
+    "stable": true
+  },
+  {
+    "address": "7vfCXTUXx5WJV5JADk17DUJ4ksgau7utNKj4b963voxs",
+    "symbol": "ETH",
+    "name": "Ether (Portal)",
+    "decimals": 8,
+    "stable": false
+  },
+  {
+    "address": "3NZ9JMVBmGAqocybic2c7LQCJScmgsAZ6vQqTDzcqmJh",
+    "symbol": "WBTC",
+    "name": "Wrapped BTC (Portal)",
+    "decimals": 8,
+    "stable": false
   }
 ]
 "#;
@@ -157,14 +179,8 @@ impl TokenRegistry {
         }
 
         Some(vec![
-            self.address_map
-                .get(pairs[0])
-                .expect("Invalid address")
-                .clone(),
-            self.address_map
-                .get(pairs[1])
-                .expect("Invalid address")
-                .clone(),
+            self.address_map.get(pairs[0])?.clone(),
+            self.address_map.get(pairs[1])?.clone(),
         ])
     }
 
@@ -256,19 +272,21 @@ pub fn get_pair_or_token_address_from_tokens(tokens: &[Token]) -> String {
     REGISTRY.get_pair_or_token_address_from_tokens(tokens)
 }
 
-pub fn get_pair_symbol_from_tokens(tokens: &[Token]) -> anyhow::Result<String> {
-    let pair_symbol = if tokens.len() == 1 {
-        format!("{}_{}", tokens[0].symbol, "USDC")
-    } else {
-        format!("{}_{}", tokens[0].symbol, tokens[1].symbol)
-    };
-
-    Ok(pair_symbol)
+/// `BASE_QUOTE` symbol of a pair, or `BASE_USDC` for a single token.
+pub fn get_pair_symbol_from_tokens(tokens: &[Token]) -> Result<String, JupSdkError> {
+    match tokens {
+        [token] => Ok(format!("{}_{}", token.symbol, "USDC")),
+        [base, quote, ..] => Ok(format!("{}_{}", base.symbol, quote.symbol)),
+        [] => Err(JupSdkError::not_found(NotFoundKind::BaseToken, "")),
+    }
 }
 
-pub fn get_pair_or_token_symbol_from_pair_address(pair_address: &str) -> anyhow::Result<String> {
-    let error_text = format!("Not support:{}", pair_address);
-    let tokens: Vec<Token> = get_by_pair_address(pair_address).expect(&error_text);
+/// Symbol of a `BASE_QUOTE` pair address; `NotFound` for unknown pairs or tokens.
+pub fn get_pair_or_token_symbol_from_pair_address(
+    pair_address: &str,
+) -> Result<String, JupSdkError> {
+    let tokens = get_by_pair_address(pair_address)
+        .ok_or_else(|| JupSdkError::not_found(NotFoundKind::Pool, pair_address))?;
     Ok(REGISTRY.get_pair_or_token_symbol_from_tokens(&tokens))
 }
 
@@ -292,6 +310,31 @@ mod tests {
         assert_eq!(pair.len(), 2);
         assert_eq!(pair[0].symbol.to_str(), "JupSOL");
         assert_eq!(pair[1].symbol.to_str(), "SOL");
+
+        assert_eq!(
+            get_pair_or_token_symbol_from_pair_address(
+                "So11111111111111111111111111111111111111112_EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v"
+            )
+            .unwrap(),
+            "SOL_USDC"
+        );
+        assert_eq!(get_pair_symbol_from_tokens(&pair[1..]).unwrap(), "SOL_USDC");
+        assert!(matches!(
+            get_pair_or_token_symbol_from_pair_address(
+                "So11111111111111111111111111111111111111112_unknown"
+            ),
+            Err(JupSdkError::NotFound {
+                kind: NotFoundKind::Pool,
+                ..
+            })
+        ));
+        assert!(matches!(
+            get_pair_symbol_from_tokens(&[]),
+            Err(JupSdkError::NotFound {
+                kind: NotFoundKind::BaseToken,
+                ..
+            })
+        ));
     }
 
     #[test]
@@ -0,0 +1,351 @@
+//! Perps trade and position-request history, and realized PnL summaries.
+
+use crate::error::{parse_f64, JupSdkError, Result};
+use crate::perps::Side;
+use crate::time::utc_date;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::str::FromStr;
+use strum::EnumString;
+use strum_macros::Display;
+
+/// One page of a paged perps endpoint (`start`/`end` query parameters).
+#[derive(Serialize, Deserialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct Page<T> {
+    /// Total number of records across all pages.
+    pub count: i32,
+    pub data_list: Vec<T>,
+}
+
+/// Raw `/trades` record.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TradeData {
+    pub action: String,
+    pub side: Side,
+    pub position_pubkey: String,
+    pub market_mint: String,
+    pub size: String,
+    pub price: String,
+    pub fee: String,
+    /// Realized PnL in USD; `null` for trades that don't reduce the position.
+    pub pnl: Option<String>,
+    pub tx_hash: String,
+    pub created_time: i64,
+}
+
+/// Raw `/position-requests` record.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PositionRequestData {
+    pub position_request_pubkey: String,
+    pub position_pubkey: String,
+    pub market_mint: String,
+    pub side: Side,
+    pub request_type: String,
+    pub request_change: String,
+    pub size_usd_delta: String,
+    pub collateral_usd_delta: String,
+    pub trigger_price: Option<String>,
+    pub executed: bool,
+    pub tx_hash: Option<String>,
+    pub created_time: i64,
+}
+
+#[derive(Display, EnumString, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum TradeAction {
+    Increase,
+    Decrease,
+    Liquidation,
+}
+
+#[derive(Display, EnumString, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case", ascii_case_insensitive)]
+pub enum RequestType {
+    Market,
+    Trigger,
+}
+
+/// An executed trade. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub action: TradeAction,
+    pub side: Side,
+    pub position_pubkey: String,
+    pub market_mint: String,
+    pub size_usd: f64,
+    pub price: f64,
+    pub fee_usd: f64,
+    pub realized_pnl_usd: Option<f64>,
+    pub tx_signature: String,
+    /// Unix seconds.
+    pub timestamp: i64,
+}
+
+/// A request to change a position: a market order or a TP/SL/limit trigger.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PositionRequest {
+    pub position_request_pubkey: String,
+    pub position_pubkey: String,
+    pub market_mint: String,
+    pub side: Side,
+    pub request_type: RequestType,
+    pub action: TradeAction,
+    pub size_usd_delta: f64,
+    pub collateral_usd_delta: f64,
+    pub trigger_price: Option<f64>,
+    pub executed: bool,
+    pub tx_signature: Option<String>,
+    /// Unix seconds.
+    pub timestamp: i64,
+}
+
+fn parse_enum<T: FromStr>(field: &str, value: &str, id: &str) -> Result<T>
+where
+    T::Err: std::fmt::Display,
+{
+    value.parse::<T>().map_err(|e| JupSdkError::Parse {
+        field: field.to_string(),
+        value: value.to_string(),
+        id: id.to_string(),
+        reason: e.to_string(),
+    })
+}
+
+impl TryFrom<TradeData> for Trade {
+    type Error = JupSdkError;
+
+    fn try_from(trade: TradeData) -> Result<Self> {
+        let id = &trade.tx_hash;
+        Ok(Self {
+            action: parse_enum("action", &trade.action, id)?,
+            side: trade.side,
+            size_usd: parse_f64("size", &trade.size, id)?,
+            price: parse_f64("price", &trade.price, id)?,
+            fee_usd: parse_f64("fee", &trade.fee, id)?,
+            realized_pnl_usd: trade
+                .pnl
+                .as_deref()
+                .map(|pnl| parse_f64("pnl", pnl, id))
+                .transpose()?,
+            position_pubkey: trade.position_pubkey,
+            market_mint: trade.market_mint,
+            timestamp: trade.created_time,
+            tx_signature: trade.tx_hash,
+        })
+    }
+}
+
+impl TryFrom<PositionRequestData> for PositionRequest {
+    type Error = JupSdkError;
+
+    fn try_from(request: PositionRequestData) -> Result<Self> {
+        let id = &request.position_request_pubkey;
+        Ok(Self {
+            request_type: parse_enum("request_type", &request.request_type, id)?,
+            action: parse_enum("request_change", &request.request_change, id)?,
+            size_usd_delta: parse_f64("size_usd_delta", &request.size_usd_delta, id)?,
+            collateral_usd_delta: parse_f64(
+                "collateral_usd_delta",
+                &request.collateral_usd_delta,
+                id,
+            )?,
+            trigger_price: request
+                .trigger_price
+                .as_deref()
+                .map(|price| parse_f64("trigger_price", price, id))
+                .transpose()?,
+            position_request_pubkey: request.position_request_pubkey,
+            position_pubkey: request.position_pubkey,
+            market_mint: request.market_mint,
+            side: request.side,
+            executed: request.executed,
+            tx_signature: request.tx_hash,
+            timestamp: request.created_time,
+        })
+    }
+}
+
+/// Calendar bucket (UTC) for `RealizedPnlSummary::by_period`.
+#[derive(Display, Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum Period {
+    Day,
+    Month,
+    Year,
+}
+
+impl Period {
+    /// Bucket key of `timestamp`: "2025-01-31", "2025-01" or "2025".
+    pub fn key(&self, timestamp: i64) -> String {
+        let (year, month, day) = utc_date(timestamp);
+        match self {
+            Period::Day => format!("{:04}-{:02}-{:02}", year, month, day),
+            Period::Month => format!("{:04}-{:02}", year, month),
+            Period::Year => format!("{:04}", year),
+        }
+    }
+}
+
+/// Totals over a set of trades. Amounts are in USD.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct PnlTotals {
+    pub trades: usize,
+    pub volume_usd: f64,
+    pub fees_usd: f64,
+    pub realized_pnl_usd: f64,
+    /// Trades that realized a profit / a loss.
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+}
+
+impl PnlTotals {
+    fn add(&mut self, trade: &Trade) {
+        self.trades += 1;
+        self.volume_usd += trade.size_usd;
+        self.fees_usd += trade.fee_usd;
+        if let Some(pnl) = trade.realized_pnl_usd {
+            self.realized_pnl_usd += pnl;
+            if pnl > 0.0 {
+                self.winning_trades += 1;
+            } else if pnl < 0.0 {
+                self.losing_trades += 1;
+            }
+        }
+    }
+}
+
+/// Realized PnL, fees and volume overall, per market mint and per period.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RealizedPnlSummary {
+    pub period: Period,
+    pub total: PnlTotals,
+    pub by_market: BTreeMap<String, PnlTotals>,
+    /// Keyed by `Period::key`, so keys sort chronologically.
+    pub by_period: BTreeMap<String, PnlTotals>,
+}
+
+impl RealizedPnlSummary {
+    pub fn new(trades: &[Trade], period: Period) -> Self {
+        let mut summary = Self {
+            period,
+            total: PnlTotals::default(),
+            by_market: BTreeMap::new(),
+            by_period: BTreeMap::new(),
+        };
+        for trade in trades {
+            summary.total.add(trade);
+            summary
+                .by_market
+                .entry(trade.market_mint.clone())
+                .or_default()
+                .add(trade);
+            summary
+                .by_period
+                .entry(period.key(trade.timestamp))
+                .or_default()
+                .add(trade);
+        }
+        summary
+    }
+}
+
+// Three SOL trades of one long: open on 2024-12-31, partial close at a profit on
+// 2025-01-01, liquidation on 2025-02-03.
+#[cfg(test)]
+pub(crate) const TRADES_FIXTURE: &str = r#"[
+  {
+    "action": "Increase",
+    "side": "long",
+    "positionPubkey": "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx",
+    "marketMint": "So11111111111111111111111111111111111111112",
+    "size": "1000",
+    "price": "140",
+    "fee": "0.6",
+    "pnl": null,
+    "txHash": "4xTradeOpen1111111111111111111111111111111111111111111111111111",
+    "createdTime": 1735653600
+  },
+  {
+    "action": "Decrease",
+    "side": "long",
+    "positionPubkey": "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx",
+    "marketMint": "So11111111111111111111111111111111111111112",
+    "size": "500",
+    "price": "150",
+    "fee": "0.3",
+    "pnl": "35.714286",
+    "txHash": "4xTradeClose111111111111111111111111111111111111111111111111111",
+    "createdTime": 1735740000
+  },
+  {
+    "action": "Liquidation",
+    "side": "long",
+    "positionPubkey": "5Lw2KBCkzBW2hX3bVNcZrcRuSxLNG6xTsdDUGPQZr6Zx",
+    "marketMint": "So11111111111111111111111111111111111111112",
+    "size": "500",
+    "price": "126.434",
+    "fee": "0.3",
+    "pnl": "-48.45",
+    "txHash": "4xTradeLiq11111111111111111111111111111111111111111111111111111",
+    "createdTime": 1738584000
+  }
+]"#;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trades() -> Vec<Trade> {
+        serde_json::from_str::<Vec<TradeData>>(TRADES_FIXTURE)
+            .unwrap()
+            .into_iter()
+            .map(|t| Trade::try_from(t).unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn test_trade_decoding() {
+        let trades = trades();
+        assert_eq!(trades[0].action, TradeAction::Increase);
+        assert_eq!(trades[0].realized_pnl_usd, None);
+        assert_eq!(trades[2].action, TradeAction::Liquidation);
+        assert_eq!(trades[2].realized_pnl_usd, Some(-48.45));
+
+        let mut raw: Vec<TradeData> = serde_json::from_str(TRADES_FIXTURE).unwrap();
+        raw[0].action = "Swap".to_string();
+        assert!(matches!(
+            Trade::try_from(raw.remove(0)),
+            Err(JupSdkError::Parse { field, .. }) if field == "action"
+        ));
+    }
+
+    #[test]
+    fn test_realized_pnl_summary_by_market_and_period() {
+        let summary = RealizedPnlSummary::new(&trades(), Period::Month);
+
+        assert_eq!(summary.total.trades, 3);
+        assert_eq!(summary.total.volume_usd, 2000.0);
+        assert!((summary.total.fees_usd - 1.2).abs() < 1e-9);
+        assert!((summary.total.realized_pnl_usd - (35.714286 - 48.45)).abs() < 1e-9);
+        assert_eq!(
+            (summary.total.winning_trades, summary.total.losing_trades),
+            (1, 1)
+        );
+        assert_eq!(summary.by_market.len(), 1);
+
+        let months: Vec<_> = summary.by_period.keys().map(String::as_str).collect();
+        assert_eq!(months, vec!["2024-12", "2025-01", "2025-02"]);
+        assert_eq!(summary.by_period["2024-12"].realized_pnl_usd, 0.0);
+        assert_eq!(summary.by_period["2025-01"].realized_pnl_usd, 35.714286);
+
+        assert_eq!(Period::Day.key(1738584000), "2025-02-03");
+        assert_eq!(Period::Year.key(0), "1970");
+        assert_eq!(Period::Day.key(951_782_400), "2000-02-29");
+    }
+}
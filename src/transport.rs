@@ -0,0 +1,350 @@
+use crate::compat::{self, BoxFuture};
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::sync::Mutex;
+use std::time::Duration;
+use strum_macros::Display;
+
+#[derive(Display, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[strum(serialize_all = "UPPERCASE")]
+pub enum Method {
+    Get,
+    Post,
+}
+
+/// A fully described HTTP request handed to a `Transport`.
+#[derive(Debug, Clone)]
+pub struct TransportRequest {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<Vec<u8>>,
+}
+
+impl TransportRequest {
+    pub fn get(url: &str) -> Self {
+        Self {
+            method: Method::Get,
+            url: url.to_string(),
+            headers: Vec::new(),
+            body: None,
+        }
+    }
+}
+
+/// A buffered HTTP response returned by a `Transport`.
+#[derive(Debug, Clone, Default)]
+pub struct TransportResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl TransportResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn is_server_error(&self) -> bool {
+        (500..600).contains(&self.status)
+    }
+
+    /// Looks up a header value (case-insensitive).
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TransportErrorKind {
+    /// Could not connect to the remote host.
+    Connect,
+    /// The transport itself gave up waiting.
+    Timeout,
+    /// The request failed while being sent.
+    Request,
+    /// Anything else (body read failures, invalid requests, ...).
+    Other,
+}
+
+#[derive(Debug)]
+pub struct TransportError {
+    kind: TransportErrorKind,
+    message: String,
+    source: Option<Box<dyn std::error::Error + Send + Sync>>,
+}
+
+impl TransportError {
+    pub fn new(kind: TransportErrorKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            source: None,
+        }
+    }
+
+    pub fn with_source(mut self, source: impl std::error::Error + Send + Sync + 'static) -> Self {
+        self.source = Some(Box::new(source));
+        self
+    }
+
+    pub fn kind(&self) -> TransportErrorKind {
+        self.kind
+    }
+
+    /// Connection, timeout and send failures are worth another attempt.
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self.kind,
+            TransportErrorKind::Connect | TransportErrorKind::Timeout | TransportErrorKind::Request
+        )
+    }
+}
+
+impl fmt::Display for TransportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} error: {}", self.kind, self.message)
+    }
+}
+
+impl std::error::Error for TransportError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        self.source
+            .as_deref()
+            .map(|e| e as &(dyn std::error::Error + 'static))
+    }
+}
+
+impl From<reqwest::Error> for TransportError {
+    fn from(e: reqwest::Error) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        let kind = if e.is_timeout() {
+            TransportErrorKind::Timeout
+        } else if e.is_connect() {
+            TransportErrorKind::Connect
+        } else if e.is_request() {
+            TransportErrorKind::Request
+        } else {
+            TransportErrorKind::Other
+        };
+        // WASM target: we can't reliably use is_connect() or is_request(),
+        // so assume it's a fetch/network issue and let the caller retry.
+        #[cfg(target_arch = "wasm32")]
+        let kind = TransportErrorKind::Request;
+
+        TransportError::new(kind, e.to_string()).with_source(e)
+    }
+}
+
+/// Sends HTTP requests on behalf of `Fetcher`.
+pub trait Transport: Send + Sync {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>>;
+}
+
+// --- reqwest ---
+
+/// Default transport backed by `reqwest::Client`.
+#[derive(Debug, Clone, Default)]
+pub struct ReqwestTransport {
+    client: reqwest::Client,
+}
+
+impl ReqwestTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_client(client: reqwest::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        let client = self.client.clone();
+        Box::pin(async move {
+            let mut builder = match request.method {
+                Method::Get => client.get(&request.url),
+                Method::Post => client.post(&request.url),
+            };
+            for (key, value) in &request.headers {
+                builder = builder.header(key, value);
+            }
+            if let Some(body) = request.body {
+                builder = builder.body(body);
+            }
+
+            let response = builder.send().await?;
+            let status = response.status().as_u16();
+            let headers = response
+                .headers()
+                .iter()
+                .filter_map(|(key, value)| {
+                    value
+                        .to_str()
+                        .ok()
+                        .map(|value| (key.as_str().to_string(), value.to_string()))
+                })
+                .collect();
+            let body = response.bytes().await?.to_vec();
+
+            Ok(TransportResponse {
+                status,
+                headers,
+                body,
+            })
+        })
+    }
+}
+
+// --- Mock ---
+
+/// A scripted reply for `MockTransport`.
+#[derive(Debug, Clone)]
+pub struct MockResponse {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+    delay: Option<Duration>,
+    error: Option<TransportErrorKind>,
+}
+
+impl MockResponse {
+    /// An empty response with the given status.
+    pub fn new(status: u16) -> Self {
+        Self {
+            status,
+            headers: Vec::new(),
+            body: Vec::new(),
+            delay: None,
+            error: None,
+        }
+    }
+
+    pub fn json<T: Serialize>(status: u16, body: &T) -> Self {
+        Self::new(status)
+            .with_header("content-type", "application/json")
+            .with_body(serde_json::to_vec(body).expect("Mock body must serialize"))
+    }
+
+    pub fn text(status: u16, body: &str) -> Self {
+        Self::new(status).with_body(body.as_bytes().to_vec())
+    }
+
+    /// Fails the request with a transport error instead of responding.
+    pub fn error(kind: TransportErrorKind) -> Self {
+        Self {
+            error: Some(kind),
+            ..Self::new(0)
+        }
+    }
+
+    pub fn with_header(mut self, key: &str, value: &str) -> Self {
+        self.headers.push((key.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn with_body(mut self, body: Vec<u8>) -> Self {
+        self.body = body;
+        self
+    }
+
+    /// Waits before replying, e.g. to trip the request timeout.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = Some(delay);
+        self
+    }
+}
+
+/// In-memory transport for offline tests.
+///
+/// Responses are queued per URL and consumed in order; the last one keeps
+/// being replayed. Unknown URLs answer `404`.
+#[derive(Debug, Default)]
+pub struct MockTransport {
+    routes: Mutex<HashMap<String, VecDeque<MockResponse>>>,
+    requests: Mutex<Vec<TransportRequest>>,
+}
+
+impl MockTransport {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builder form of `push`.
+    pub fn on(self, url: &str, response: MockResponse) -> Self {
+        self.push(url, response);
+        self
+    }
+
+    /// Queues a response for `url`.
+    pub fn push(&self, url: &str, response: MockResponse) {
+        self.routes
+            .lock()
+            .unwrap()
+            .entry(url.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// All requests received so far, in order.
+    pub fn requests(&self) -> Vec<TransportRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+
+    pub fn request_count(&self, url: &str) -> usize {
+        self.requests
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|request| request.url == url)
+            .count()
+    }
+
+    fn next_response(&self, url: &str) -> MockResponse {
+        let mut routes = self.routes.lock().unwrap();
+        match routes.get_mut(url) {
+            Some(queue) if queue.len() > 1 => queue.pop_front().unwrap(),
+            Some(queue) if !queue.is_empty() => queue[0].clone(),
+            _ => MockResponse::text(404, &format!("No mock response for {}", url)),
+        }
+    }
+}
+
+impl Transport for MockTransport {
+    fn send(
+        &self,
+        request: TransportRequest,
+    ) -> BoxFuture<'_, Result<TransportResponse, TransportError>> {
+        let response = self.next_response(&request.url);
+        self.requests.lock().unwrap().push(request);
+
+        Box::pin(async move {
+            if let Some(delay) = response.delay {
+                compat::sleep(delay).await;
+            }
+            if let Some(kind) = response.error {
+                return Err(TransportError::new(kind, "Mock transport error"));
+            }
+            Ok(TransportResponse {
+                status: response.status,
+                headers: response.headers,
+                body: response.body,
+            })
+        })
+    }
+}